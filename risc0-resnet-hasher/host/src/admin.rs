@@ -0,0 +1,50 @@
+use clap::{Args, Subcommand};
+use eyre::Result;
+
+use crate::config::{parse_credits, NetworkArgs};
+use crate::contracts::format_credits;
+use crate::tx::send_call;
+
+#[derive(Args, Debug)]
+pub struct AdminArgs {
+    /// Key holding `DEFAULT_ADMIN_ROLE` on JobManager.
+    #[arg(long, env = "ADMIN_PRIVATE_KEY", hide_env_values = true)]
+    private_key: String,
+
+    #[command(subcommand)]
+    command: AdminCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AdminCommand {
+    /// Set the minimum ProviderRegistry stake required to accept jobs.
+    SetMinStake {
+        /// Minimum stake in whole GPUCredit.
+        #[arg(long)]
+        amount: String,
+    },
+}
+
+pub async fn run(network: &NetworkArgs, args: AdminArgs) -> Result<()> {
+    let admin_signer = network.signer(&args.private_key)?;
+    network.print_summary("Admin", &admin_signer);
+    let job_manager_admin_contract = network.job_manager(admin_signer);
+
+    match args.command {
+        AdminCommand::SetMinStake { amount } => {
+            let new_min_stake = parse_credits(&amount)?;
+            let old_min_stake = job_manager_admin_contract.min_provider_stake_required().call().await?;
+            println!("\nCurrent minProviderStakeRequired: {}", format_credits(old_min_stake));
+            println!("Setting minProviderStakeRequired to {}...", format_credits(new_min_stake));
+            let set_receipt = send_call(
+                &network.tx,
+                job_manager_admin_contract.client_ref(),
+                "setMinProviderStakeRequired",
+                job_manager_admin_contract.set_min_provider_stake_required(new_min_stake),
+            )
+            .await?;
+            println!("Minimum provider stake updated. Tx: {:?}", set_receipt.transaction_hash);
+        }
+    }
+    Ok(())
+}
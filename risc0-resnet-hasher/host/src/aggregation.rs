@@ -0,0 +1,137 @@
+use ethers::types::U256;
+use eyre::Result;
+use risc0_aggregation::{
+    merkle_path, merkle_root, GuestState, SetInclusionReceipt, SetInclusionReceiptVerifierParameters,
+};
+use risc0_ethereum_contracts::encode_seal;
+use risc0_zkvm::sha::{Digest, Digestible};
+use risc0_zkvm::{compute_image_id, default_prover, ExecutorEnv, ProverOpts, Receipt, ReceiptClaim};
+
+use crate::config::SignerClient;
+use crate::contracts::SetVerifierContract;
+use crate::journal::{journal_digest, preflight_verify};
+use crate::revert::describe_contract_err;
+use crate::tx::{send_call, TxArgs};
+
+/// A verified succinct job receipt waiting for the next batch.
+struct PendingJob {
+    job_id: U256,
+    image_id: Digest,
+    receipt: Receipt,
+    claim: ReceiptClaim,
+}
+
+/// Everything needed to call `submitProofAndClaim` for one job of an aggregated batch.
+pub struct AggregatedJob {
+    /// Set inclusion seal (set verifier selector, Merkle path, no root seal).
+    pub seal: Vec<u8>,
+    pub journal_digest: [u8; 32],
+}
+
+/// Batches succinct job receipts into a single Groth16 proof of a Merkle root.
+///
+/// The set-builder guest is proven once per batch and its root is published with
+/// `submitMerkleRoot`. Each job then only carries its Merkle path, so `RiscZeroSetVerifier` checks
+/// a handful of hashes per job instead of a full Groth16 verification.
+pub struct ProofAggregator {
+    set_verifier: SetVerifierContract<SignerClient>,
+    set_builder_elf: Vec<u8>,
+    set_builder_id: Digest,
+    pending: Vec<PendingJob>,
+}
+
+impl ProofAggregator {
+    /// Creates an aggregator, checking that `set_builder_elf` is the image the set verifier expects.
+    pub async fn new(set_verifier: SetVerifierContract<SignerClient>, set_builder_elf: Vec<u8>) -> Result<Self> {
+        let set_builder_id = compute_image_id(&set_builder_elf).map_err(|e| eyre::eyre!("Set builder image ID: {:?}", e))?;
+        let (on_chain_image_id, image_url) =
+            set_verifier.image_info().call().await.map_err(|e| eyre::eyre!("imageInfo(): {}", describe_contract_err(&e)))?;
+        if on_chain_image_id != <[u8; 32]>::from(set_builder_id) {
+            eyre::bail!(
+                "Set builder ELF has image ID {} but the set verifier expects 0x{} (published at {}).",
+                set_builder_id, hex::encode(on_chain_image_id), image_url
+            );
+        }
+        Ok(Self { set_verifier, set_builder_elf, set_builder_id, pending: Vec::new() })
+    }
+
+    /// Adds a succinct receipt for `job_id`, verified locally against the job's `image_id`.
+    pub fn push(&mut self, job_id: U256, image_id: Digest, receipt: Receipt) -> Result<()> {
+        receipt.inner.succinct().map_err(|_| eyre::eyre!("Job {} receipt is not succinct; only succinct receipts can be aggregated.", job_id))?;
+        preflight_verify(&receipt, image_id)?;
+        let claim = receipt
+            .claim()
+            .map_err(|e| eyre::eyre!("Job {} receipt claim: {:?}", job_id, e))?
+            .value()
+            .map_err(|e| eyre::eyre!("Job {} receipt claim is pruned: {:?}", job_id, e))?;
+        self.pending.push(PendingJob { job_id, image_id, receipt, claim });
+        Ok(())
+    }
+
+    /// Proves the set builder over all pending receipts, publishes the root with `send_call` and
+    /// returns each job's set inclusion seal, or why the set verifier rejects it. Every seal is
+    /// encoded before the root is sent, and the pending queue is cleared only once the root is
+    /// on-chain, so a failure leaves the batch intact for another attempt.
+    pub async fn submit_batch(&mut self, tx_args: &TxArgs) -> Result<Vec<(U256, Result<AggregatedJob>)>> {
+        if self.pending.is_empty() {
+            eyre::bail!("No receipts to aggregate.");
+        }
+        let claims: Vec<ReceiptClaim> = self.pending.iter().map(|job| job.claim.clone()).collect();
+        let leaves: Vec<Digest> = claims.iter().map(|claim| claim.digest()).collect();
+        let expected_root = merkle_root(&leaves);
+
+        println!("\nAggregating {} receipts with set builder {}...", self.pending.len(), self.set_builder_id);
+        let input = GuestState::initial(self.set_builder_id)
+            .into_input(claims, true)
+            .map_err(|e| eyre::eyre!("Set builder input: {:?}", e))?;
+        let mut env_builder = ExecutorEnv::builder();
+        for job in &self.pending {
+            env_builder.add_assumption(job.receipt.clone());
+        }
+        let env = env_builder
+            .write(&input)
+            .map_err(|e| eyre::eyre!("Env write: {:?}", e))?
+            .build()
+            .map_err(|e| eyre::eyre!("Env build: {:?}", e))?;
+        let root_receipt = default_prover()
+            .prove_with_opts(env, &self.set_builder_elf, &ProverOpts::groth16())
+            .map_err(|e| eyre::eyre!("R0 proving (set builder): {:?}", e))?
+            .receipt;
+
+        let state = GuestState::decode(&root_receipt.journal.bytes).map_err(|e| eyre::eyre!("Set builder journal: {:?}", e))?;
+        let root = state.mmr.finalized_root().ok_or_else(|| eyre::eyre!("Set builder did not finalize the Merkle root."))?;
+        if root != expected_root {
+            eyre::bail!("Set builder root {} does not match the locally computed root {}.", root, expected_root);
+        }
+
+        let verifier_parameters = SetInclusionReceiptVerifierParameters { image_id: self.set_builder_id }.digest();
+        let mut aggregated = Vec::with_capacity(self.pending.len());
+        for (index, job) in self.pending.iter().enumerate() {
+            let inclusion_receipt = SetInclusionReceipt::from_path_with_verifier_params(
+                job.claim.clone(), merkle_path(&leaves, index), verifier_parameters);
+            let seal = encode_seal(&inclusion_receipt).map_err(|e| eyre::eyre!("Job {} seal encoding: {}", job.job_id, e))?;
+            aggregated.push(AggregatedJob { seal, journal_digest: journal_digest(&job.receipt.journal.bytes) });
+        }
+
+        let root_seal = encode_seal(&root_receipt).map_err(|e| eyre::eyre!("Root seal encoding: {}", e))?;
+        println!("Submitting Merkle root {} to the set verifier...", root);
+        let submit_root_call = self.set_verifier.submit_merkle_root(root.into(), root_seal.into());
+        let submit_root_receipt = send_call(tx_args, self.set_verifier.client_ref(), &format!("submitMerkleRoot {}", root), submit_root_call).await?;
+        println!("Merkle root published. Tx: {:?}", submit_root_receipt.transaction_hash);
+        let pending = std::mem::take(&mut self.pending);
+
+        // eth_call against the set verifier: free, and catches a bad path before JobManager does.
+        let mut results = Vec::with_capacity(aggregated.len());
+        for (job, aggregated_job) in pending.into_iter().zip(aggregated) {
+            let verified = self
+                .set_verifier
+                .verify(aggregated_job.seal.clone().into(), job.image_id.into(), aggregated_job.journal_digest)
+                .call()
+                .await
+                .map(|()| aggregated_job)
+                .map_err(|e| eyre::eyre!("Job {} set inclusion seal rejected by the set verifier: {}", job.job_id, describe_contract_err(&e)));
+            results.push((job.job_id, verified));
+        }
+        Ok(results)
+    }
+}
@@ -0,0 +1,80 @@
+use std::sync::Arc;
+
+use ethers::prelude::*;
+use ethers::utils::keccak256;
+use eyre::Result;
+use methods::{JobBinding, JobOutputs};
+use risc0_zkvm::Receipt;
+
+use crate::config::{NetworkArgs, SignerClient};
+use crate::contracts::JobCreatedFilter;
+
+/// `keccak256(blockhash(n - 1) ‖ jobId ‖ providerAddr)`, packed like `abi.encodePacked`.
+///
+/// Same derivation as the coordinator service, so the provider can compute it on its own.
+pub fn job_seed(previous_block_hash: H256, job_id: U256, provider: Address) -> [u8; 32] {
+    let mut packed = Vec::with_capacity(32 + 32 + 20);
+    packed.extend_from_slice(previous_block_hash.as_bytes());
+    let mut job_id_bytes = [0u8; 32];
+    job_id.to_big_endian(&mut job_id_bytes);
+    packed.extend_from_slice(&job_id_bytes);
+    packed.extend_from_slice(provider.as_bytes());
+    keccak256(packed)
+}
+
+/// Builds the binding for `job_id` from its `JobCreated` event, as the guest will commit it.
+pub async fn fetch_job_binding(
+    network: &NetworkArgs,
+    signer: Arc<SignerClient>,
+    job_id: U256,
+    provider: Address,
+) -> Result<JobBinding> {
+    let (event, meta) = network.find_job_event::<JobCreatedFilter>(signer.clone(), job_id, |event| event.job_id == job_id).await?;
+
+    let created_block = meta.block_number.as_u64();
+    let previous_block = signer
+        .get_block(created_block.saturating_sub(1))
+        .await?
+        .ok_or_else(|| eyre::eyre!("Block {} not found.", created_block.saturating_sub(1)))?;
+    let previous_block_hash = previous_block.hash.ok_or_else(|| eyre::eyre!("Block {} has no hash yet.", previous_block.number.unwrap_or_default()))?;
+
+    let mut job_id_bytes = [0u8; 32];
+    job_id.to_big_endian(&mut job_id_bytes);
+    Ok(JobBinding {
+        job_id: job_id_bytes,
+        chain_id: network.chain_id,
+        job_manager: network.job_manager_address.to_fixed_bytes(),
+        client: event.client.to_fixed_bytes(),
+        seed: job_seed(previous_block_hash, job_id, provider),
+    })
+}
+
+/// Fails if the journal of `receipt` was not produced for `expected`.
+pub fn check_journal_binding(receipt: &Receipt, expected: &JobBinding) -> Result<()> {
+    let outputs: JobOutputs = receipt.journal.decode().map_err(|e| eyre::eyre!("Journal decode failed: {:?}", e))?;
+    if outputs.binding != *expected {
+        eyre::bail!(
+            "Receipt is bound to job 0x{} on chain {} (JobManager 0x{}), not to the job being claimed.",
+            hex::encode(outputs.binding.job_id),
+            outputs.binding.chain_id,
+            hex::encode(outputs.binding.job_manager)
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Vector from the coordinator's
+    /// `ethers.solidityPacked(["bytes32", "uint256", "address"], [0xabab..ab, 42, 0x..cd])`.
+    #[test]
+    fn seed_matches_coordinator_derivation() {
+        let provider: Address = "0x00000000000000000000000000000000000000cd".parse().unwrap();
+        assert_eq!(
+            hex::encode(job_seed(H256::repeat_byte(0xab), U256::from(42u64), provider)),
+            "ae4b16c1be10d5ee0025f0fc7d5b171126757ee320cd654b3c687195027dfebf"
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::{Args, Subcommand};
+use ethers::prelude::*;
+use eyre::Result;
+
+use methods::RISC0_RESNET_HASHER_ID;
+
+use crate::config::{parse_credits, NetworkArgs, SignerClient};
+use crate::contracts::{format_credits, method_id_to_bytes_array};
+use crate::job_data::JobInputArgs;
+use crate::result::{fetch_result, JobResult};
+use crate::tx::send_call;
+use crate::storage::StorageArgs;
+use crate::verify::verify_job;
+
+const ONE_DAY_IN_SECONDS_U64: u64 = 24 * 60 * 60;
+
+#[derive(Args, Debug)]
+pub struct ClientArgs {
+    #[arg(long, env = "CLIENT_PRIVATE_KEY", hide_env_values = true)]
+    private_key: String,
+
+    #[command(subcommand)]
+    command: ClientCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ClientCommand {
+    /// Escrow GPUCredit and post a new job to JobManager.
+    CreateJob {
+        /// Reward in whole GPUCredit.
+        #[arg(long, default_value = "10")]
+        reward: String,
+        /// CID of job data that is already stored; without it the inputs below are uploaded.
+        #[arg(long)]
+        cid: Option<String>,
+        /// Seconds from now until the proof deadline.
+        #[arg(long, default_value_t = ONE_DAY_IN_SECONDS_U64)]
+        deadline_secs: u64,
+        #[command(flatten)]
+        inputs: JobInputArgs,
+        #[command(flatten)]
+        storage: StorageArgs,
+    },
+    /// Store job data without creating a job and print its CID.
+    Upload {
+        #[command(flatten)]
+        inputs: JobInputArgs,
+        #[command(flatten)]
+        storage: StorageArgs,
+    },
+    /// Download a completed job's result by `resultDataCID` and check it against the journal hash
+    /// JobManager verified.
+    FetchResult {
+        #[arg(long)]
+        job_id: u64,
+        /// Also write the result (bincode `JobResult`) to this file.
+        #[arg(long)]
+        out: Option<PathBuf>,
+        #[command(flatten)]
+        storage: StorageArgs,
+    },
+    /// Independently verify a completed job: the submitted seal, the published result and the
+    /// uploaded job data must agree.
+    VerifyJob {
+        job_id: u64,
+        #[command(flatten)]
+        storage: StorageArgs,
+    },
+    /// Cancel a job that has not been accepted (or whose deadline has passed) and get refunded.
+    CancelJob {
+        #[arg(long)]
+        job_id: u64,
+    },
+}
+
+pub async fn run(network: &NetworkArgs, args: ClientArgs) -> Result<()> {
+    let client_signer = network.signer(&args.private_key)?;
+    network.print_summary("Client", &client_signer);
+
+    match args.command {
+        ClientCommand::CreateJob { reward, cid, deadline_secs, inputs, storage } => {
+            let job_reward = parse_credits(&reward)?;
+            let cid = match cid {
+                Some(cid) => cid,
+                None => upload_job_data(&inputs, &storage).await?,
+            };
+            let job_id = create_job(network, client_signer, &cid, job_reward, deadline_secs).await?;
+            println!("Using Job ID: {}", job_id);
+        }
+        ClientCommand::Upload { inputs, storage } => {
+            upload_job_data(&inputs, &storage).await?;
+        }
+        ClientCommand::FetchResult { job_id, out, storage } => {
+            let result = fetch_result(network, client_signer, storage.open()?.as_ref(), U256::from(job_id)).await?;
+            print_result(&result);
+            if let Some(out) = out {
+                std::fs::write(&out, result.to_bytes()?).map_err(|e| eyre::eyre!("Cannot write {}: {}", out.display(), e))?;
+                println!("Result written to {}", out.display());
+            }
+        }
+        ClientCommand::VerifyJob { job_id, storage } => {
+            verify_job(network, client_signer, storage.open()?.as_ref(), U256::from(job_id)).await?;
+        }
+        ClientCommand::CancelJob { job_id } => {
+            cancel_job(network, client_signer, U256::from(job_id)).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn upload_job_data(inputs: &JobInputArgs, storage: &StorageArgs) -> Result<String> {
+    let job_data = inputs.job_data()?.to_bytes()?;
+    println!("\nUploading {} bytes of job data...", job_data.len());
+    let cid = storage.open()?.put(&job_data).await?;
+    println!("Job data CID: {}", cid);
+    Ok(cid)
+}
+
+async fn create_job(
+    network: &NetworkArgs,
+    client_signer: Arc<SignerClient>,
+    job_cid_str: &str,
+    job_reward: U256,
+    deadline_secs: u64,
+) -> Result<U256> {
+    let gpu_credit_client_contract = network.gpu_credit(client_signer.clone());
+    let job_manager_client_contract = network.job_manager(client_signer);
+
+    let risc0_method_id_as_bytes_array: [u8; 32] = method_id_to_bytes_array(&RISC0_RESNET_HASHER_ID);
+    let current_timestamp_secs = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let deadline_timestamp_ethers = U256::from(current_timestamp_secs + deadline_secs);
+
+    println!("\nClient approving {} GPUCredit for JobManager...", format_credits(job_reward));
+    let approve_call = gpu_credit_client_contract.approve(network.job_manager_address, job_reward);
+    send_call(&network.tx, gpu_credit_client_contract.client_ref(), "GPUCredit approval for JobManager", approve_call).await?;
+    println!("GPUCredit approved.");
+    println!("Client creating job on JobManager...");
+    let create_job_call = job_manager_client_contract.create_job(
+        job_cid_str.to_string(), job_reward, deadline_timestamp_ethers, risc0_method_id_as_bytes_array);
+    let job_creation_receipt = send_call(&network.tx, job_manager_client_contract.client_ref(), "createJob", create_job_call).await?;
+    println!("Job created! Tx hash: {:?}", job_creation_receipt.transaction_hash);
+
+    let job_created_event_signature_topic0 = job_manager_client_contract.abi().event("JobCreated")?.signature();
+    let parsed_job_id_opt: Option<U256> = job_creation_receipt.logs.iter()
+        .find(|log_entry| log_entry.address == network.job_manager_address
+            && log_entry.topics.len() > 1
+            && log_entry.topics[0] == job_created_event_signature_topic0)
+        .map(|log_entry| U256::from_big_endian(log_entry.topics[1].as_bytes()));
+    parsed_job_id_opt.ok_or_else(|| eyre::eyre!("Failed to parse JobId. Logs: {:?}", job_creation_receipt.logs))
+}
+
+fn print_result(result: &JobResult) {
+    println!("\n--- Job {} Result (provider {:?}) ---", result.job_id, result.provider);
+    println!("Image Batch Hash:      0x{}", hex::encode(result.outputs.image_batch_hash));
+    println!("Model Weights Hash:    0x{}", hex::encode(result.outputs.model_weights_hash));
+    println!("Preprocessing:         {:?}", result.outputs.preprocessing);
+    println!("Sampled Images:        {:?} of {}", result.outputs.sampled_indices, result.outputs.batch_size);
+    println!("Predicted Classes:     {:?}", result.predicted_classes);
+    for line in &result.logs {
+        println!("Log: {}", line);
+    }
+}
+
+async fn cancel_job(
+    network: &NetworkArgs,
+    client_signer: Arc<SignerClient>,
+    job_id: U256,
+) -> Result<()> {
+    let job_manager_client_contract = network.job_manager(client_signer);
+
+    println!("\nClient cancelling Job ID: {}...", job_id);
+    let cancel_call = job_manager_client_contract.cancel_job(job_id);
+    let cancel_receipt = send_call(&network.tx, job_manager_client_contract.client_ref(), &format!("cancelJob for Job ID {}", job_id), cancel_call).await?;
+    println!("Job ID: {} cancelled and escrow refunded. Tx: {:?}", job_id, cancel_receipt.transaction_hash);
+    Ok(())
+}
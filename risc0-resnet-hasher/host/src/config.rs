@@ -0,0 +1,107 @@
+use std::sync::Arc;
+
+use clap::Args;
+use ethers::prelude::*;
+use eyre::Result;
+
+use crate::contracts::{GPUCreditContract, JobManagerContract, ProviderRegistryContract};
+use crate::tx::TxArgs;
+
+// Constants
+pub const DEFAULT_ARBITRUM_SEPOLIA_CHAIN_ID: u64 = 421614;
+
+/// Signing client shared by every role. Each subcommand builds one from its own key only.
+pub type SignerClient = SignerMiddleware<Provider<Http>, LocalWallet>;
+
+/// RPC endpoint and deployed contract addresses. Every value can come from `.env`.
+#[derive(Args, Debug, Clone)]
+pub struct NetworkArgs {
+    /// JSON-RPC endpoint of the target chain.
+    #[arg(long, env = "TESTNET_RPC_URL")]
+    pub rpc_url: String,
+
+    #[arg(long, env = "CHAIN_ID", default_value_t = DEFAULT_ARBITRUM_SEPOLIA_CHAIN_ID)]
+    pub chain_id: u64,
+
+    #[arg(long, env = "GPU_CREDIT_ADDRESS")]
+    pub gpu_credit_address: Address,
+
+    #[arg(long, env = "JOB_MANAGER_ADDRESS")]
+    pub job_manager_address: Address,
+
+    /// Block JobManager was deployed in; event lookups by job ID start here. Required, since
+    /// public RPCs refuse to scan a chain's whole history.
+    #[arg(long, env = "JOB_MANAGER_DEPLOY_BLOCK")]
+    pub job_manager_deploy_block: u64,
+
+    /// Maximum number of blocks requested per `eth_getLogs` call in event lookups by job ID.
+    #[arg(long, env = "LOG_BLOCK_RANGE", default_value_t = 10_000)]
+    pub log_block_range: u64,
+
+    /// Can be the zero address if JobManager is deployed without a registry.
+    #[arg(long, env = "PROVIDER_REGISTRY_ADDRESS", default_value = "0x0000000000000000000000000000000000000000")]
+    pub provider_registry_address: Address,
+
+    #[command(flatten)]
+    pub tx: TxArgs,
+}
+
+impl NetworkArgs {
+    /// Connects to the RPC endpoint and wraps it with a signer for `private_key`.
+    pub fn signer(&self, private_key: &str) -> Result<Arc<SignerClient>> {
+        let http_provider = Provider::<Http>::try_from(self.rpc_url.as_str())?;
+        let wallet = private_key.parse::<LocalWallet>()?.with_chain_id(self.chain_id);
+        Ok(Arc::new(SignerMiddleware::new(http_provider, wallet)))
+    }
+
+    pub fn gpu_credit(&self, signer: Arc<SignerClient>) -> GPUCreditContract<SignerClient> {
+        GPUCreditContract::new(self.gpu_credit_address, signer)
+    }
+
+    pub fn job_manager(&self, signer: Arc<SignerClient>) -> JobManagerContract<SignerClient> {
+        JobManagerContract::new(self.job_manager_address, signer)
+    }
+
+    pub fn provider_registry(&self, signer: Arc<SignerClient>) -> ProviderRegistryContract<SignerClient> {
+        ProviderRegistryContract::new(self.provider_registry_address, signer)
+    }
+
+    /// Finds the JobManager event `D` for `job_id`, indexed as its first topic, scanning from the
+    /// deploy block to the head in `--log-block-range` chunks.
+    pub async fn find_job_event<D: EthEvent>(&self, signer: Arc<SignerClient>, job_id: U256, is_for_job: impl Fn(&D) -> bool) -> Result<(D, LogMeta)> {
+        let job_manager = self.job_manager(signer.clone());
+        let head = signer.get_block_number().await?.as_u64();
+        let mut from_block = self.job_manager_deploy_block;
+        while from_block <= head {
+            let to_block = head.min(from_block + self.log_block_range.saturating_sub(1));
+            let found = job_manager
+                .event::<D>()
+                .from_block(from_block)
+                .to_block(to_block)
+                .topic1(job_id)
+                .query_with_meta()
+                .await?
+                .into_iter()
+                .find(|(event, _)| is_for_job(event));
+            if let Some(found) = found {
+                return Ok(found);
+            }
+            from_block = to_block + 1;
+        }
+        eyre::bail!("No {} event for Job ID {} since block {}.", D::name(), job_id, self.job_manager_deploy_block)
+    }
+
+    pub fn print_summary(&self, role: &str, signer: &SignerClient) {
+        println!("Using RPC URL: {}", self.rpc_url);
+        println!("Chain ID: {}", self.chain_id);
+        println!("{} Address: {:?}", role, signer.address());
+        println!("GPUCredit Address: {:?}", self.gpu_credit_address);
+        println!("JobManager Address: {:?}", self.job_manager_address);
+        println!("ProviderRegistry Address: {:?}", self.provider_registry_address);
+    }
+}
+
+/// Parses a whole-token amount such as `"10"` or `"0.5"` into 18-decimal base units.
+pub fn parse_credits(amount: &str) -> Result<U256> {
+    Ok(ethers::utils::parse_ether(amount)?)
+}
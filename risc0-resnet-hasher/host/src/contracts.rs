@@ -0,0 +1,62 @@
+use ethers::prelude::*;
+
+// Contract Bindings
+abigen!(
+    GPUCreditContract,
+    "./abi/GPUCredit.json",
+    event_derives (serde::Deserialize, serde::Serialize)
+);
+abigen!(
+    JobManagerContract,
+    "./abi/JobManager.json",
+    event_derives (serde::Deserialize, serde::Serialize)
+);
+abigen!(
+    ProviderRegistryContract,
+    "./abi/ProviderRegistry.json",
+    event_derives (serde::Deserialize, serde::Serialize)
+);
+
+// RiscZeroSetVerifier, from the vendored `src/risc0/IRiscZeroSetVerifier.sol`.
+abigen!(
+    SetVerifierContract,
+    r#"[
+        function submitMerkleRoot(bytes32 root, bytes seal) external
+        function containsRoot(bytes32 root) external view returns (bool)
+        function imageInfo() external view returns (bytes32, string)
+        function verify(bytes seal, bytes32 imageId, bytes32 journalDigest) external view
+        error VerificationFailed()
+        error SelectorMismatch(bytes4 received, bytes4 expected)
+    ]"#
+);
+
+/// `JobManager.JobStatus` discriminants as returned by `getJob`.
+pub const JOB_STATUS_CREATED: u8 = 0;
+pub const JOB_STATUS_ACCEPTED: u8 = 1;
+pub const JOB_STATUS_COMPLETED: u8 = 2;
+
+// Helper
+pub fn method_id_to_bytes_array(method_id: &[u32; 8]) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    for (i, word) in method_id.iter().enumerate() {
+        bytes[i * 4..(i + 1) * 4].copy_from_slice(&word.to_le_bytes());
+    }
+    bytes
+}
+
+/// Formats a token amount (18 decimals) for log output.
+pub fn format_credits(amount: U256) -> String {
+    ethers::utils::format_units(amount, "ether").unwrap_or_else(|_| amount.to_string())
+}
+
+/// Arbitrum's virtual NodeInterface, reachable only through `eth_call`.
+pub const NODE_INTERFACE_ADDRESS: Address = H160([
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xc8,
+]);
+
+abigen!(
+    NodeInterface,
+    r#"[
+        function gasEstimateComponents(address to, bool contractCreation, bytes data) external payable returns (uint64 gasEstimate, uint64 gasEstimateForL1, uint256 baseFee, uint256 l1BaseFeeEstimate)
+    ]"#
+);
@@ -0,0 +1,535 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use clap::Args;
+use ethers::prelude::*;
+use eyre::Result;
+use redb::{Database, ReadableTable, TableDefinition};
+use serde::{Deserialize, Serialize};
+
+use crate::binding::job_seed;
+use crate::config::NetworkArgs;
+use crate::contracts::{format_credits, JobCreatedFilter, JobManagerContract, ProviderRegistryContract, JOB_STATUS_CREATED};
+use crate::server::{JobState, JobStatusReport};
+
+/// Job ID -> JSON-encoded [Assignment].
+const ASSIGNMENTS: TableDefinition<u64, &str> = TableDefinition::new("assignments");
+/// Job ID -> JSON-encoded [UnassignedJob], for jobs seen but not yet given to a provider.
+const UNASSIGNED: TableDefinition<u64, &str> = TableDefinition::new("unassigned");
+/// Scan progress, keyed by name.
+const META: TableDefinition<&str, u64> = TableDefinition::new("meta");
+const LAST_PROCESSED_BLOCK: &str = "last_processed_block";
+
+/// Options for the job coordinator.
+#[derive(Args, Debug, Clone)]
+pub struct CoordinatorArgs {
+    /// Provider agents jobs can be relayed to, as `address=base_url`. Only these are assigned
+    /// work, and only while ProviderRegistry reports enough stake for them.
+    #[arg(long = "provider-endpoint", env = "PROVIDER_ENDPOINTS", value_delimiter = ',', value_parser = parse_endpoint, required = true)]
+    provider_endpoints: Vec<(Address, String)>,
+    /// Embedded database holding assignments and the last processed block.
+    #[arg(long, env = "COORDINATOR_DB", default_value = "coordinator.redb")]
+    db: PathBuf,
+    /// Seconds to sleep between polls once caught up with the chain head.
+    #[arg(long, default_value_t = 15)]
+    poll_interval_secs: u64,
+    /// Maximum number of blocks requested per `eth_getLogs` call.
+    #[arg(long, default_value_t = 1000)]
+    block_range: u64,
+    /// First block to scan when the database is new; defaults to the current head.
+    #[arg(long)]
+    start_block: Option<u64>,
+    /// Give up relaying a job after this many failed POSTs.
+    #[arg(long, default_value_t = 5)]
+    max_relay_attempts: u32,
+}
+
+fn parse_endpoint(value: &str) -> Result<(Address, String)> {
+    let (address, url) = value
+        .split_once('=')
+        .ok_or_else(|| eyre::eyre!("expected `address=base_url`, got `{}`", value))?;
+    Ok((address.trim().parse()?, url.trim().trim_end_matches('/').to_string()))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RelayStatus {
+    Pending,
+    Relayed,
+    Failed,
+}
+
+/// One job handed to one provider.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Assignment {
+    pub job_id: U256,
+    pub client: Address,
+    pub cid: String,
+    pub provider: Address,
+    pub endpoint: String,
+    pub seed: H256,
+    pub created_block: u64,
+    pub status: RelayStatus,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    /// Last state reported by the provider's `/jobs/{id}`; absent for agents without it.
+    #[serde(default)]
+    pub provider_state: Option<JobState>,
+}
+
+/// A `JobCreated` that could not be assigned yet, e.g. while no provider had enough stake.
+/// Retried on every poll until it is assigned or leaves the `Created` status.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnassignedJob {
+    pub job_id: U256,
+    pub client: Address,
+    pub cid: String,
+    pub created_block: u64,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+}
+
+impl UnassignedJob {
+    fn from_event(event: &JobCreatedFilter, created_block: u64) -> Self {
+        Self {
+            job_id: event.job_id,
+            client: event.client,
+            cid: event.job_data_cid.clone(),
+            created_block,
+            attempts: 0,
+            last_error: None,
+        }
+    }
+}
+
+/// Redb key of a job; IDs beyond `u64` are refused rather than truncated.
+fn job_key(job_id: U256) -> Result<u64> {
+    u64::try_from(job_id).map_err(|_| eyre::eyre!("Job ID {} does not fit the assignment store's u64 keys.", job_id))
+}
+
+/// Body POSTed to a provider's `/receive-job`, same shape the TypeScript coordinator sent.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiveJobRequest {
+    /// Decimal job ID.
+    pub job_id: String,
+    pub cid: String,
+    /// `0x`-prefixed hex seed.
+    pub seed: String,
+}
+
+/// Assignments and scan progress in a local redb file, so restarts neither lose nor repeat work.
+pub struct AssignmentStore {
+    db: Database,
+}
+
+impl AssignmentStore {
+    pub fn open(path: &Path) -> Result<Self> {
+        let db = Database::create(path)?;
+        let txn = db.begin_write()?;
+        txn.open_table(ASSIGNMENTS)?;
+        txn.open_table(UNASSIGNED)?;
+        txn.open_table(META)?;
+        txn.commit()?;
+        Ok(Self { db })
+    }
+
+    pub fn get(&self, job_id: U256) -> Result<Option<Assignment>> {
+        let txn = self.db.begin_read()?;
+        let table = txn.open_table(ASSIGNMENTS)?;
+        let assignment = table.get(job_key(job_id)?)?.map(|json| serde_json::from_str(json.value())).transpose()?;
+        Ok(assignment)
+    }
+
+    /// Stores `assignment`, which also takes its job off the unassigned list.
+    pub fn put(&self, assignment: &Assignment) -> Result<()> {
+        let key = job_key(assignment.job_id)?;
+        let json = serde_json::to_string(assignment)?;
+        let txn = self.db.begin_write()?;
+        txn.open_table(ASSIGNMENTS)?.insert(key, json.as_str())?;
+        txn.open_table(UNASSIGNED)?.remove(key)?;
+        txn.commit()?;
+        Ok(())
+    }
+
+    pub fn put_unassigned(&self, job: &UnassignedJob) -> Result<()> {
+        let json = serde_json::to_string(job)?;
+        let txn = self.db.begin_write()?;
+        txn.open_table(UNASSIGNED)?.insert(job_key(job.job_id)?, json.as_str())?;
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Takes `job` back from the provider it was assigned to, so the next poll assigns it again.
+    pub fn unassign(&self, job: &UnassignedJob) -> Result<()> {
+        let key = job_key(job.job_id)?;
+        let json = serde_json::to_string(job)?;
+        let txn = self.db.begin_write()?;
+        txn.open_table(ASSIGNMENTS)?.remove(key)?;
+        txn.open_table(UNASSIGNED)?.insert(key, json.as_str())?;
+        txn.commit()?;
+        Ok(())
+    }
+
+    pub fn remove_unassigned(&self, job_id: U256) -> Result<()> {
+        let txn = self.db.begin_write()?;
+        txn.open_table(UNASSIGNED)?.remove(job_key(job_id)?)?;
+        txn.commit()?;
+        Ok(())
+    }
+
+    pub fn unassigned(&self) -> Result<Vec<UnassignedJob>> {
+        let txn = self.db.begin_read()?;
+        let table = txn.open_table(UNASSIGNED)?;
+        let mut jobs = Vec::new();
+        for entry in table.iter()? {
+            let (_, json) = entry?;
+            jobs.push(serde_json::from_str(json.value())?);
+        }
+        Ok(jobs)
+    }
+
+    pub fn all(&self) -> Result<Vec<Assignment>> {
+        let txn = self.db.begin_read()?;
+        let table = txn.open_table(ASSIGNMENTS)?;
+        let mut assignments = Vec::new();
+        for entry in table.iter()? {
+            let (_, json) = entry?;
+            assignments.push(serde_json::from_str(json.value())?);
+        }
+        Ok(assignments)
+    }
+
+    pub fn last_processed_block(&self) -> Result<Option<u64>> {
+        let txn = self.db.begin_read()?;
+        let table = txn.open_table(META)?;
+        Ok(table.get(LAST_PROCESSED_BLOCK)?.map(|block| block.value()))
+    }
+
+    pub fn set_last_processed_block(&self, block: u64) -> Result<()> {
+        let txn = self.db.begin_write()?;
+        txn.open_table(META)?.insert(LAST_PROCESSED_BLOCK, block)?;
+        txn.commit()?;
+        Ok(())
+    }
+}
+
+/// Chain handles, HTTP client and store shared by every assignment.
+struct Coordinator<'a> {
+    network: &'a NetworkArgs,
+    args: &'a CoordinatorArgs,
+    client: Arc<Provider<Http>>,
+    job_manager: JobManagerContract<Provider<Http>>,
+    provider_registry: ProviderRegistryContract<Provider<Http>>,
+    store: AssignmentStore,
+    http: reqwest::Client,
+}
+
+/// Watches `JobCreated`, assigns each job to an eligible provider and relays it over HTTP.
+pub async fn run(network: &NetworkArgs, args: CoordinatorArgs) -> Result<()> {
+    let client = Arc::new(Provider::<Http>::try_from(network.rpc_url.as_str())?);
+    let coordinator = Coordinator {
+        network,
+        args: &args,
+        job_manager: JobManagerContract::new(network.job_manager_address, client.clone()),
+        provider_registry: ProviderRegistryContract::new(network.provider_registry_address, client.clone()),
+        client,
+        store: AssignmentStore::open(&args.db)?,
+        http: reqwest::Client::builder().timeout(Duration::from_secs(30)).build()?,
+    };
+
+    let mut next_block = match coordinator.store.last_processed_block()? {
+        Some(last) => last + 1,
+        None => match args.start_block {
+            Some(start) => start,
+            None => coordinator.client.get_block_number().await?.as_u64(),
+        },
+    };
+    println!("\n--- Coordinator ---");
+    println!("Using RPC URL: {}", network.rpc_url);
+    println!("JobManager Address: {:?}", network.job_manager_address);
+    println!("ProviderRegistry Address: {:?}", network.provider_registry_address);
+    println!("Assignment DB: {}", args.db.display());
+    for (provider, endpoint) in &args.provider_endpoints {
+        println!("Provider {:?} at {}", provider, endpoint);
+    }
+    println!("Watching JobCreated from block {}", next_block);
+
+    loop {
+        match coordinator.poll(next_block).await {
+            Ok(Some(to_block)) => next_block = to_block + 1,
+            Ok(None) => tokio::time::sleep(Duration::from_secs(args.poll_interval_secs)).await,
+            Err(e) => {
+                println!("⚠️ Poll from block {} failed: {:#}; retrying in {}s.", next_block, e, args.poll_interval_secs);
+                tokio::time::sleep(Duration::from_secs(args.poll_interval_secs)).await;
+            }
+        }
+    }
+}
+
+impl Coordinator<'_> {
+    /// Retries earlier jobs, then assigns those created from `next_block` over at most
+    /// `--block-range` blocks. Returns the last block handled, or `None` once past the head.
+    async fn poll(&self, next_block: u64) -> Result<Option<u64>> {
+        self.retry_unassigned_jobs().await?;
+        self.retry_failed_relays().await?;
+        self.track_relayed_jobs().await?;
+
+        let head = self.client.get_block_number().await?.as_u64();
+        if next_block > head {
+            return Ok(None);
+        }
+        let to_block = head.min(next_block + self.args.block_range.saturating_sub(1));
+
+        let events = self
+            .job_manager
+            .event::<JobCreatedFilter>()
+            .from_block(next_block)
+            .to_block(to_block)
+            .query_with_meta()
+            .await?;
+        for (event, meta) in events {
+            if let Err(e) = job_key(event.job_id) {
+                println!("❌ Skipping JobCreated: {}", e);
+                continue;
+            }
+            if self.store.get(event.job_id)?.is_some() {
+                continue;
+            }
+            println!(
+                "\n🎉 JobCreated: Job ID {}, client {:?}, CID {}, reward {} GPUCredit",
+                event.job_id, event.client, event.job_data_cid, format_credits(event.max_payment_gpu_credit)
+            );
+            // Persisted before the attempt, so a failure here or a crash is retried on the next poll.
+            let mut job = UnassignedJob::from_event(&event, meta.block_number.as_u64());
+            self.store.put_unassigned(&job)?;
+            self.try_assign(&mut job).await?;
+        }
+
+        self.store.set_last_processed_block(to_block)?;
+        Ok(Some(to_block))
+    }
+
+    /// Assigns `job`, or records why it could not be assigned so the next poll retries it.
+    async fn try_assign(&self, job: &mut UnassignedJob) -> Result<()> {
+        job.attempts += 1;
+        if let Err(e) = self.assign_job(job).await {
+            println!("❌ Could not assign job {} (attempt {}): {:?}", job.job_id, job.attempts, e);
+            job.last_error = Some(e.to_string());
+            self.store.put_unassigned(job)?;
+        }
+        Ok(())
+    }
+
+    /// Retries jobs left unassigned by an earlier poll, dropping those no longer open.
+    async fn retry_unassigned_jobs(&self) -> Result<()> {
+        for mut job in self.store.unassigned()? {
+            let on_chain_job = self.job_manager.get_job(job.job_id).call().await?;
+            if on_chain_job.status != JOB_STATUS_CREATED {
+                println!("Dropping unassigned job {}: status is now {}.", job.job_id, on_chain_job.status);
+                self.store.remove_unassigned(job.job_id)?;
+                continue;
+            }
+            self.try_assign(&mut job).await?;
+        }
+        Ok(())
+    }
+
+    async fn assign_job(&self, job: &UnassignedJob) -> Result<()> {
+        let created_block = job.created_block;
+        let eligible = self.eligible_providers().await?;
+        let (provider, endpoint) = pick_provider(&eligible, &self.store.all()?)
+            .ok_or_else(|| eyre::eyre!("No provider endpoint currently has the minimum stake."))?;
+
+        let previous_block = self
+            .client
+            .get_block(created_block.saturating_sub(1))
+            .await?
+            .ok_or_else(|| eyre::eyre!("Block {} not found.", created_block.saturating_sub(1)))?;
+        let previous_block_hash = previous_block.hash.ok_or_else(|| eyre::eyre!("Block {} has no hash yet.", created_block.saturating_sub(1)))?;
+        let seed = H256::from(job_seed(previous_block_hash, job.job_id, provider));
+        println!("Assigning Job ID {} to provider {:?}. Seed: {:?}", job.job_id, provider, seed);
+
+        let mut assignment = Assignment {
+            job_id: job.job_id,
+            client: job.client,
+            cid: job.cid.clone(),
+            provider,
+            endpoint,
+            seed,
+            created_block,
+            status: RelayStatus::Pending,
+            attempts: 0,
+            last_error: None,
+            provider_state: None,
+        };
+        self.store.put(&assignment)?;
+        relay(&mut assignment, &self.http).await;
+        self.store.put(&assignment)
+    }
+
+    /// Configured providers that ProviderRegistry reports as registered with at least the
+    /// JobManager minimum stake. Without a registry every configured provider is eligible.
+    async fn eligible_providers(&self) -> Result<Vec<(Address, String)>> {
+        if self.network.provider_registry_address == Address::zero() {
+            return Ok(self.args.provider_endpoints.clone());
+        }
+        let min_stake = self.job_manager.min_provider_stake_required().call().await?;
+        let mut eligible = Vec::new();
+        for (provider, endpoint) in &self.args.provider_endpoints {
+            let info = self.provider_registry.get_provider_info(*provider).call().await?;
+            if info.exists && info.stake_amount >= min_stake {
+                eligible.push((*provider, endpoint.clone()));
+            } else {
+                println!("Provider {:?} not eligible: stake {} < minimum {}", provider, format_credits(info.stake_amount), format_credits(min_stake));
+            }
+        }
+        Ok(eligible)
+    }
+
+    /// Relays again to providers that did not take a job, and once `--max-relay-attempts` are
+    /// used up puts the job back up for assignment, possibly to another provider.
+    async fn retry_failed_relays(&self) -> Result<()> {
+        for mut assignment in self.store.all()? {
+            if !matches!(assignment.status, RelayStatus::Pending | RelayStatus::Failed) {
+                continue;
+            }
+            if assignment.attempts < self.args.max_relay_attempts {
+                relay(&mut assignment, &self.http).await;
+                self.store.put(&assignment)?;
+            } else {
+                println!(
+                    "⚠️ Provider {:?} did not take job {} after {} attempts; assigning it again.",
+                    assignment.provider, assignment.job_id, assignment.attempts
+                );
+                self.store.unassign(&UnassignedJob {
+                    job_id: assignment.job_id,
+                    client: assignment.client,
+                    cid: assignment.cid.clone(),
+                    created_block: assignment.created_block,
+                    attempts: 0,
+                    last_error: assignment.last_error.clone(),
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Polls `/jobs/{id}` for relayed jobs the provider has not finished and records changes.
+    async fn track_relayed_jobs(&self) -> Result<()> {
+        for mut assignment in self.store.all()? {
+            if assignment.status != RelayStatus::Relayed || assignment.provider_state.is_some_and(JobState::is_final) {
+                continue;
+            }
+            let url = format!("{}/jobs/{}", assignment.endpoint, assignment.job_id);
+            let report = match self.http.get(&url).send().await {
+                Ok(response) if response.status().is_success() => response.json::<JobStatusReport>().await.ok(),
+                _ => None,
+            };
+            let Some(report) = report else { continue };
+            if assignment.provider_state != Some(report.state) {
+                println!("Job {} at provider {:?}: {:?}", assignment.job_id, assignment.provider, report.state);
+                assignment.provider_state = Some(report.state);
+                assignment.last_error = report.error;
+                self.store.put(&assignment)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The eligible provider with the fewest assignments so far; ties go to the earlier endpoint.
+fn pick_provider(eligible: &[(Address, String)], assignments: &[Assignment]) -> Option<(Address, String)> {
+    eligible
+        .iter()
+        .min_by_key(|(provider, _)| assignments.iter().filter(|a| a.provider == *provider).count())
+        .cloned()
+}
+
+/// POSTs the job to the provider and records the outcome on `assignment`.
+async fn relay(assignment: &mut Assignment, http: &reqwest::Client) {
+    assignment.attempts += 1;
+    let body = ReceiveJobRequest {
+        job_id: assignment.job_id.to_string(),
+        cid: assignment.cid.clone(),
+        seed: format!("{:?}", assignment.seed),
+    };
+    let url = format!("{}/receive-job", assignment.endpoint);
+    let outcome = match http.post(&url).json(&body).send().await {
+        Ok(response) if response.status().is_success() => Ok(()),
+        Ok(response) => Err(format!("{} returned {}: {}", url, response.status(), response.text().await.unwrap_or_default())),
+        Err(e) => Err(format!("{}: {}", url, e)),
+    };
+    match outcome {
+        Ok(()) => {
+            println!("✅ Job {} relayed to {}", assignment.job_id, url);
+            assignment.status = RelayStatus::Relayed;
+            assignment.last_error = None;
+        }
+        Err(e) => {
+            println!("❌ Relaying job {} failed (attempt {}): {}", assignment.job_id, assignment.attempts, e);
+            assignment.status = RelayStatus::Failed;
+            assignment.last_error = Some(e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assignment(job_id: u64, provider: Address) -> Assignment {
+        Assignment {
+            job_id: U256::from(job_id),
+            client: Address::zero(),
+            cid: "QmTest".into(),
+            provider,
+            endpoint: "http://localhost:3001".into(),
+            seed: H256::zero(),
+            created_block: 1,
+            status: RelayStatus::Relayed,
+            attempts: 1,
+            last_error: None,
+            provider_state: None,
+        }
+    }
+
+    #[test]
+    fn store_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("coordinator.redb");
+        {
+            let store = AssignmentStore::open(&path).unwrap();
+            let unassigned = |job_id: u64| UnassignedJob {
+                job_id: U256::from(job_id),
+                client: Address::zero(),
+                cid: "QmTest".into(),
+                created_block: 1,
+                attempts: 1,
+                last_error: Some("No provider endpoint currently has the minimum stake.".into()),
+            };
+            store.put_unassigned(&unassigned(7)).unwrap();
+            store.put_unassigned(&unassigned(8)).unwrap();
+            store.put(&assignment(7, Address::repeat_byte(1))).unwrap();
+            store.set_last_processed_block(99).unwrap();
+        }
+        let store = AssignmentStore::open(&path).unwrap();
+        assert_eq!(store.get(U256::from(7)).unwrap().unwrap().provider, Address::repeat_byte(1));
+        assert!(store.get(U256::from(8)).unwrap().is_none());
+        // Assigning job 7 took it off the unassigned list; job 8 is still waiting for a provider.
+        let unassigned: Vec<U256> = store.unassigned().unwrap().iter().map(|job| job.job_id).collect();
+        assert_eq!(unassigned, vec![U256::from(8)]);
+        assert_eq!(store.last_processed_block().unwrap(), Some(99));
+        assert!(store.get(U256::MAX).is_err());
+    }
+
+    #[test]
+    fn picks_least_loaded_provider() {
+        let a = (Address::repeat_byte(1), "http://a".to_string());
+        let b = (Address::repeat_byte(2), "http://b".to_string());
+        let eligible = vec![a.clone(), b.clone()];
+        assert_eq!(pick_provider(&eligible, &[]), Some(a.clone()));
+        assert_eq!(pick_provider(&eligible, &[assignment(1, a.0)]), Some(b));
+        assert_eq!(pick_provider(&[], &[]), None);
+    }
+}
@@ -0,0 +1,308 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use clap::Args;
+use ethers::prelude::*;
+use eyre::Result;
+
+use methods::RISC0_RESNET_HASHER_ID;
+
+use crate::config::{parse_credits, NetworkArgs, SignerClient};
+use crate::contracts::{format_credits, method_id_to_bytes_array, JobCreatedFilter, JOB_STATUS_ACCEPTED, JOB_STATUS_CREATED};
+use crate::fs::write_atomic;
+use crate::pricing::{CostModel, PricingArgs};
+use crate::proof_store::ProofStore;
+use crate::prover::{execute_job, prove_job};
+use crate::provider::{accept_or_resume, load_job_inputs, resume_pending_proofs, submit_proof};
+use crate::schedule::{check_deadline, ScheduleArgs, Scheduler};
+use crate::storage::{ContentStore, StorageArgs};
+
+/// Options for the long-running provider agent.
+#[derive(Args, Debug, Clone)]
+pub struct DaemonArgs {
+    /// Ignore jobs paying less than this many whole GPUCredit.
+    #[arg(long, default_value = "0")]
+    min_reward: String,
+    /// Ignore jobs whose deadline is closer than this many seconds.
+    #[arg(long, default_value_t = 3600)]
+    min_time_left_secs: u64,
+    /// Seconds to sleep between polls once caught up with the chain head.
+    #[arg(long, default_value_t = 15)]
+    poll_interval_secs: u64,
+    /// Maximum number of blocks requested per `eth_getLogs` call.
+    #[arg(long, default_value_t = 1000)]
+    block_range: u64,
+    /// File holding the last fully processed block.
+    #[arg(long, default_value = "provider-agent.state")]
+    state_file: PathBuf,
+    /// First block to scan when no state file exists yet; defaults to the current head.
+    #[arg(long)]
+    start_block: Option<u64>,
+    /// Proofs are kept here until their job is completed, and resubmitted on restart.
+    #[arg(long, env = "PROOF_DIR", default_value = "receipts")]
+    proof_dir: PathBuf,
+    #[command(flatten)]
+    storage: StorageArgs,
+    #[command(flatten)]
+    pricing: PricingArgs,
+    #[command(flatten)]
+    schedule: ScheduleArgs,
+}
+
+/// Watches `JobCreated` and accepts, proves and submits every job this guest can serve at a
+/// profit and in time.
+pub async fn run(network: &NetworkArgs, provider_signer: Arc<SignerClient>, args: DaemonArgs) -> Result<()> {
+    let min_reward = parse_credits(&args.min_reward)?;
+    let cost_model = args.pricing.cost_model()?;
+    let method_id = method_id_to_bytes_array(&RISC0_RESNET_HASHER_ID);
+    let mut scheduler = Scheduler::open(&args.schedule, method_id)?;
+    let store = args.storage.open()?;
+    let proof_store = ProofStore::new(&args.proof_dir);
+
+    let last_processed = read_last_processed_block(&args.state_file)?;
+    let mut next_block = match last_processed {
+        Some(last) => last + 1,
+        None => match args.start_block {
+            Some(start) => start,
+            None => provider_signer.get_block_number().await?.as_u64(),
+        },
+    };
+    println!("\n--- Provider Agent ---");
+    println!("Watching JobCreated for methodId 0x{} from block {}", hex::encode(method_id), next_block);
+    println!("Min reward: {} GPUCredit, min time left: {}s", format_credits(min_reward), args.min_time_left_secs);
+    println!(
+        "Cost model: {} GPUCredit per million padded cycles, {} per segment, {} per Groth16 proof, {} in transactions; {}% margin",
+        format_credits(cost_model.per_mcycle),
+        format_credits(cost_model.per_segment),
+        format_credits(cost_model.groth16),
+        format_credits(cost_model.tx_cost),
+        cost_model.min_margin_pct
+    );
+    println!("Deadline margin: {}s", scheduler.deadline_margin_secs());
+    resume_pending_proofs(network, provider_signer.clone(), &proof_store, store.as_ref()).await?;
+
+    // Jobs that failed but may still succeed, with the block they were created in.
+    let mut retries: Vec<(JobCreatedFilter, u64)> = Vec::new();
+    let mut saved_block = last_processed;
+    loop {
+        let mut jobs = std::mem::take(&mut retries);
+        let scanned = match scan_range(network, provider_signer.clone(), method_id, next_block, args.block_range).await {
+            Ok(Some((to_block, events))) => {
+                jobs.extend(events);
+                Some(to_block)
+            }
+            Ok(None) => None,
+            Err(e) => {
+                println!("⚠️ Could not read JobCreated from block {}: {:#}; retrying in {}s.", next_block, e, args.poll_interval_secs);
+                None
+            }
+        };
+
+        for (event, block) in jobs {
+            if let Some(reason) = skip_reason(&event, method_id, min_reward, args.min_time_left_secs)? {
+                println!("Skipping job {}: {}", event.job_id, reason);
+                continue;
+            }
+            if let Err(e) = handle_job(network, provider_signer.clone(), &proof_store, store.as_ref(), &cost_model, &mut scheduler, &event).await {
+                if still_ours_to_take(network, provider_signer.clone(), event.job_id).await {
+                    println!("❌ Job {} failed: {:?}; retrying after the next poll.", event.job_id, e);
+                    retries.push((event, block));
+                } else {
+                    println!("❌ Job {} failed: {:?}", event.job_id, e);
+                }
+            }
+        }
+
+        if let Some(to_block) = scanned {
+            next_block = to_block + 1;
+        }
+        let processed = last_processed_block(next_block, &retries);
+        if saved_block != Some(processed) {
+            write_last_processed_block(&args.state_file, processed)?;
+            saved_block = Some(processed);
+        }
+        if scanned.is_none() {
+            tokio::time::sleep(Duration::from_secs(args.poll_interval_secs)).await;
+        }
+    }
+}
+
+/// Reads this guest's `JobCreated` events from `from_block` over at most `block_range` blocks,
+/// with the block each was emitted in. `None` once `from_block` is past the head.
+async fn scan_range(
+    network: &NetworkArgs,
+    provider_signer: Arc<SignerClient>,
+    method_id: [u8; 32],
+    from_block: u64,
+    block_range: u64,
+) -> Result<Option<(u64, Vec<(JobCreatedFilter, u64)>)>> {
+    let head = provider_signer.get_block_number().await?.as_u64();
+    if from_block > head {
+        return Ok(None);
+    }
+    let to_block = head.min(from_block + block_range.saturating_sub(1));
+    let events = network
+        .job_manager(provider_signer)
+        .event::<JobCreatedFilter>()
+        .from_block(from_block)
+        .to_block(to_block)
+        .topic3(H256::from(method_id))
+        .query_with_meta()
+        .await?;
+    Ok(Some((to_block, events.into_iter().map(|(event, meta)| (event, meta.block_number.as_u64())).collect())))
+}
+
+/// Whether a failed job is still open or accepted by this provider, so retrying it may pay off.
+/// An RPC error counts as yes: the failure may have been the RPC too.
+async fn still_ours_to_take(network: &NetworkArgs, provider_signer: Arc<SignerClient>, job_id: U256) -> bool {
+    match network.job_manager(provider_signer.clone()).get_job(job_id).call().await {
+        Ok(job) => {
+            job.status == JOB_STATUS_CREATED || (job.status == JOB_STATUS_ACCEPTED && job.provider == provider_signer.address())
+        }
+        Err(_) => true,
+    }
+}
+
+/// The last block whose jobs are all handled: just before `next_block`, or before the earliest
+/// job still waiting for a retry, so a restart scans that job again.
+fn last_processed_block(next_block: u64, retries: &[(JobCreatedFilter, u64)]) -> u64 {
+    retries.iter().map(|(_, block)| *block).fold(next_block, u64::min).saturating_sub(1)
+}
+
+/// Explains why `event` is not worth executing, if it is not: another guest, too little reward
+/// or too little time.
+fn skip_reason(event: &JobCreatedFilter, method_id: [u8; 32], min_reward: U256, min_time_left_secs: u64) -> Result<Option<String>> {
+    if event.method_id != method_id {
+        return Ok(Some(format!("methodId 0x{} is not this guest", hex::encode(event.method_id))));
+    }
+    if event.max_payment_gpu_credit < min_reward {
+        return Ok(Some(format!("reward {} below minimum {}", format_credits(event.max_payment_gpu_credit), format_credits(min_reward))));
+    }
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    if event.deadline_timestamp < U256::from(now + min_time_left_secs) {
+        return Ok(Some(format!("deadline {} is less than {}s away", event.deadline_timestamp, min_time_left_secs)));
+    }
+    Ok(None)
+}
+
+async fn handle_job(
+    network: &NetworkArgs,
+    provider_signer: Arc<SignerClient>,
+    proof_store: &ProofStore,
+    store: &dyn ContentStore,
+    cost_model: &CostModel,
+    scheduler: &mut Scheduler,
+    event: &JobCreatedFilter,
+) -> Result<()> {
+    let job_id = event.job_id;
+    let job_manager_provider_contract = network.job_manager(provider_signer.clone());
+    let stored_proof = proof_store.get(job_id)?;
+
+    // Price and time an open job by executing it before committing to it; a job already ours is
+    // finished whatever it costs, but is still executed so proving can be timed against the
+    // deadline.
+    let on_chain_job = job_manager_provider_contract.get_job(job_id).call().await?;
+    let open = on_chain_job.status == JOB_STATUS_CREATED;
+    let ours = on_chain_job.status == JOB_STATUS_ACCEPTED && on_chain_job.provider == provider_signer.address();
+    let mut to_prove = None;
+    if stored_proof.is_none() && (open || ours) {
+        let inputs = load_job_inputs(network, provider_signer.clone(), store, job_id).await?;
+        let report = execute_job(job_id, &inputs)?;
+        if open {
+            if let Some(reason) = cost_model.refuse_reason(event.max_payment_gpu_credit, &report) {
+                println!("Skipping job {}: {}", job_id, reason);
+                return Ok(());
+            }
+            if let Some(reason) = scheduler.refuse_reason(event.deadline_timestamp, &report)? {
+                println!("Skipping job {}: {}", job_id, reason);
+                return Ok(());
+            }
+        }
+        to_prove = Some((inputs, scheduler.estimate(&report)));
+    }
+
+    // After a restart the last block is scanned again, so a job may already be ours.
+    if !accept_or_resume(network, provider_signer.clone(), job_id).await? {
+        return Ok(());
+    }
+
+    let mut proof = match (stored_proof, to_prove) {
+        (Some(proof), _) => {
+            println!("Using stored proof for Job ID {}.", job_id);
+            proof
+        }
+        (None, Some((zk_guest_inputs, estimate))) => {
+            prove_job(job_id, &zk_guest_inputs, event.deadline_timestamp, &estimate, proof_store, scheduler)?
+        }
+        (None, None) => eyre::bail!("Job ID {} changed state while it was being checked; retry it.", job_id),
+    };
+    check_deadline(job_id, event.deadline_timestamp, "submission", 0.0)?;
+    submit_proof(network, provider_signer, &mut proof, proof_store, store).await
+}
+
+fn read_last_processed_block(path: &Path) -> Result<Option<u64>> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents.trim().parse()?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn write_last_processed_block(path: &Path, block: u64) -> Result<()> {
+    write_atomic(path, block.to_string().as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job_created(max_payment_gpu_credit: u64, deadline_timestamp: u64, method_id: [u8; 32]) -> JobCreatedFilter {
+        JobCreatedFilter {
+            job_id: U256::from(3),
+            client: Address::zero(),
+            job_data_cid: "bafkrei".into(),
+            max_payment_gpu_credit: U256::from(max_payment_gpu_credit),
+            deadline_timestamp: U256::from(deadline_timestamp),
+            method_id,
+        }
+    }
+
+    #[test]
+    fn skips_other_guests_low_rewards_and_close_deadlines() {
+        let guest = [7; 32];
+        let in_a_day = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 86_400;
+        assert_eq!(skip_reason(&job_created(10, in_a_day, guest), guest, U256::from(10), 3600).unwrap(), None);
+
+        let other_guest = skip_reason(&job_created(10, in_a_day, [8; 32]), guest, U256::from(10), 3600).unwrap().unwrap();
+        assert!(other_guest.contains("methodId"), "{}", other_guest);
+        let low_reward = skip_reason(&job_created(9, in_a_day, guest), guest, U256::from(10), 3600).unwrap().unwrap();
+        assert!(low_reward.contains("below minimum"), "{}", low_reward);
+        let close_deadline = skip_reason(&job_created(10, in_a_day, guest), guest, U256::from(10), 2 * 86_400).unwrap().unwrap();
+        assert!(close_deadline.contains("less than"), "{}", close_deadline);
+    }
+
+    #[test]
+    fn state_file_resumes_after_last_processed_block() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("provider-agent.state");
+        assert_eq!(read_last_processed_block(&path).unwrap(), None);
+
+        write_last_processed_block(&path, 41).unwrap();
+        write_last_processed_block(&path, 42).unwrap();
+        assert_eq!(read_last_processed_block(&path).unwrap(), Some(42));
+        assert!(!path.with_extension("tmp").exists());
+
+        std::fs::write(&path, "not a block").unwrap();
+        assert!(read_last_processed_block(&path).is_err());
+    }
+
+    #[test]
+    fn jobs_waiting_for_a_retry_hold_back_the_processed_block() {
+        let guest = [7; 32];
+        assert_eq!(last_processed_block(101, &[]), 100);
+        let retries = [(job_created(10, 0, guest), 90), (job_created(10, 0, guest), 95)];
+        assert_eq!(last_processed_block(101, &retries), 89);
+        assert_eq!(last_processed_block(0, &[]), 0);
+    }
+}
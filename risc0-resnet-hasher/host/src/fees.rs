@@ -0,0 +1,210 @@
+use std::sync::Arc;
+
+use clap::Args;
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::utils::{format_units, parse_units};
+use eyre::Result;
+
+use crate::config::SignerClient;
+use crate::contracts::{NodeInterface, NODE_INTERFACE_ADDRESS};
+use crate::revert::describe_contract_err;
+
+/// Arbitrum One, Nova and Sepolia, whose fees include an L1 data component.
+pub const ARBITRUM_CHAIN_IDS: [u64; 3] = [42161, 42170, 421614];
+
+/// Fee limits for every transaction sent.
+#[derive(Args, Debug, Clone)]
+pub struct FeeArgs {
+    /// Highest fee per gas to pay, in gwei. Transactions are not sent while the base fee is
+    /// above it, and stuck ones are not bumped past it.
+    #[arg(long, global = true, env = "MAX_FEE_GWEI")]
+    pub max_fee_gwei: Option<String>,
+    /// Priority fee per gas, in gwei. Arbitrum ignores it; other chains need one to get mined.
+    #[arg(long, global = true, env = "PRIORITY_FEE_GWEI", default_value = "0")]
+    pub priority_fee_gwei: String,
+    /// Refuse transactions expected to cost more than this much ETH.
+    #[arg(long, global = true, env = "MAX_TX_COST_ETH")]
+    pub max_tx_cost_eth: Option<String>,
+    /// Extra gas limit over the L1 data component, in percent. It follows the L1 base fee,
+    /// which can move between estimate and inclusion.
+    #[arg(long, global = true, default_value_t = 25)]
+    pub l1_gas_headroom_pct: u64,
+}
+
+impl FeeArgs {
+    pub fn max_fee_per_gas(&self) -> Result<Option<U256>> {
+        self.max_fee_gwei.as_deref().map(|gwei| Ok(parse_units(gwei, "gwei")?.into())).transpose()
+    }
+
+    fn priority_fee_per_gas(&self) -> Result<U256> {
+        Ok(parse_units(&self.priority_fee_gwei, "gwei")?.into())
+    }
+
+    fn max_tx_cost(&self) -> Result<Option<U256>> {
+        self.max_tx_cost_eth.as_deref().map(|eth| Ok(parse_units(eth, "ether")?.into())).transpose()
+    }
+}
+
+/// Gas of one transaction, split the way Arbitrum charges it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GasComponents {
+    /// Gas for executing the call on L2.
+    pub l2_gas: U256,
+    /// Gas charged for posting the calldata to L1, in L2 gas units. Zero off Arbitrum.
+    pub l1_gas: U256,
+    pub base_fee: U256,
+    /// NodeInterface's estimate of the L1 base fee the data component is priced at.
+    pub l1_base_fee: Option<U256>,
+}
+
+/// Asks NodeInterface for the gas of `request` on Arbitrum chains, and `eth_estimateGas`
+/// elsewhere. Fails, with the decoded reason, if the call would revert.
+pub async fn estimate_gas_components(client: &SignerClient, request: &TypedTransaction) -> Result<GasComponents> {
+    if !ARBITRUM_CHAIN_IDS.contains(&client.signer().chain_id()) {
+        let l2_gas = client.estimate_gas(request, None).await.map_err(|e| eyre::eyre!("{}", describe_contract_err(&ContractError::<SignerClient>::from_middleware_error(e))))?;
+        return Ok(GasComponents { l2_gas, l1_gas: U256::zero(), base_fee: latest_base_fee(client).await?, l1_base_fee: None });
+    }
+
+    let to = match request.to() {
+        Some(NameOrAddress::Address(to)) => *to,
+        _ => eyre::bail!("NodeInterface estimates need a contract address to call."),
+    };
+    let node_interface = NodeInterface::new(NODE_INTERFACE_ADDRESS, Arc::new(client.provider().clone()));
+    let (gas_estimate, gas_estimate_for_l1, base_fee, l1_base_fee_estimate) = node_interface
+        .gas_estimate_components(to, false, request.data().cloned().unwrap_or_default())
+        .from(client.address())
+        .value(request.value().copied().unwrap_or_default())
+        .call()
+        .await
+        .map_err(|e| eyre::eyre!("{}", describe_contract_err(&e)))?;
+    Ok(GasComponents {
+        l2_gas: U256::from(gas_estimate.saturating_sub(gas_estimate_for_l1)),
+        l1_gas: U256::from(gas_estimate_for_l1),
+        base_fee,
+        l1_base_fee: Some(l1_base_fee_estimate),
+    })
+}
+
+/// Base fee of the latest block, or the gas price on chains without one.
+pub async fn latest_base_fee(client: &SignerClient) -> Result<U256> {
+    let latest = client.get_block(BlockNumber::Latest).await?.ok_or_else(|| eyre::eyre!("Latest block not found."))?;
+    match latest.base_fee_per_gas {
+        Some(base_fee) => Ok(base_fee),
+        None => Ok(client.get_gas_price().await?),
+    }
+}
+
+/// Gas limit and fees for one transaction, within the operator's caps.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeEstimate {
+    pub components: GasComponents,
+    pub gas_limit: U256,
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+}
+
+impl FeeEstimate {
+    /// Adds 20% headroom to the L2 gas and `--l1-gas-headroom-pct` to the L1 gas, and offers up
+    /// to twice the base fee plus the tip, never more than `--max-fee-gwei`.
+    pub fn new(components: GasComponents, fee_args: &FeeArgs) -> Result<Self> {
+        let priority_fee = fee_args.priority_fee_per_gas()?;
+        let mut max_fee: U256 = components.base_fee * 2 + priority_fee;
+        if let Some(cap) = fee_args.max_fee_per_gas()? {
+            if components.base_fee + priority_fee > cap {
+                eyre::bail!("Base fee {} gwei plus tip is above --max-fee-gwei {}.", gwei(components.base_fee), gwei(cap));
+            }
+            max_fee = max_fee.min(cap);
+        }
+        let gas_limit = components.l2_gas * 120 / 100 + components.l1_gas * (100 + fee_args.l1_gas_headroom_pct) / 100;
+        let estimate = Self { components, gas_limit, max_fee_per_gas: max_fee, max_priority_fee_per_gas: priority_fee };
+        if let Some(cap) = fee_args.max_tx_cost()? {
+            if estimate.expected_cost() > cap {
+                eyre::bail!("Expected cost {} ETH is above --max-tx-cost-eth {}.", eth(estimate.expected_cost()), eth(cap));
+            }
+        }
+        Ok(estimate)
+    }
+
+    /// What the transaction should cost if the estimate holds: all its gas at the base fee
+    /// plus the tip.
+    pub fn expected_cost(&self) -> U256 {
+        let price = (self.components.base_fee + self.max_priority_fee_per_gas).min(self.max_fee_per_gas);
+        (self.components.l2_gas + self.components.l1_gas) * price
+    }
+
+    /// The most the transaction can cost: its whole gas limit at the max fee.
+    pub fn max_cost(&self) -> U256 {
+        self.gas_limit * self.max_fee_per_gas
+    }
+
+    pub fn apply(&self, request: &mut TypedTransaction) {
+        request.set_gas(self.gas_limit);
+        match request {
+            TypedTransaction::Eip1559(inner) => {
+                inner.max_fee_per_gas = Some(self.max_fee_per_gas);
+                inner.max_priority_fee_per_gas = Some(self.max_priority_fee_per_gas);
+            }
+            _ => {
+                request.set_gas_price((self.components.base_fee + self.max_priority_fee_per_gas).min(self.max_fee_per_gas));
+            }
+        }
+    }
+
+    pub fn print_report(&self, what: &str) {
+        println!(
+            "Gas for {}: {} L2 execution + {} L1 data (limit {}), base fee {} gwei{}",
+            what,
+            self.components.l2_gas,
+            self.components.l1_gas,
+            self.gas_limit,
+            gwei(self.components.base_fee),
+            self.components.l1_base_fee.map(|l1| format!(", L1 base fee estimate {} gwei", gwei(l1))).unwrap_or_default()
+        );
+        println!(
+            "Expected cost of {}: {} ETH ({} ETH for L1 data), at most {} ETH at max fee {} gwei",
+            what,
+            eth(self.expected_cost()),
+            eth(self.components.l1_gas * self.components.base_fee),
+            eth(self.max_cost()),
+            gwei(self.max_fee_per_gas)
+        );
+    }
+}
+
+fn gwei(amount: U256) -> String {
+    format_units(amount, "gwei").unwrap_or_else(|_| amount.to_string())
+}
+
+fn eth(amount: U256) -> String {
+    format_units(amount, "ether").unwrap_or_else(|_| amount.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fee_args(max_fee_gwei: Option<&str>, max_tx_cost_eth: Option<&str>) -> FeeArgs {
+        FeeArgs {
+            max_fee_gwei: max_fee_gwei.map(str::to_string),
+            priority_fee_gwei: "0".to_string(),
+            max_tx_cost_eth: max_tx_cost_eth.map(str::to_string),
+            l1_gas_headroom_pct: 50,
+        }
+    }
+
+    #[test]
+    fn estimate_splits_headroom_and_respects_caps() {
+        // A Groth16 submission: modest execution, large calldata.
+        let components = GasComponents { l2_gas: 300_000.into(), l1_gas: 200_000.into(), base_fee: 100_000_000.into(), l1_base_fee: None };
+        let estimate = FeeEstimate::new(components.clone(), &fee_args(None, None)).unwrap();
+        assert_eq!(estimate.gas_limit, U256::from(360_000 + 300_000));
+        assert_eq!(estimate.max_fee_per_gas, U256::from(200_000_000u64));
+        assert_eq!(estimate.expected_cost(), U256::from(500_000u64 * 100_000_000));
+
+        let capped = FeeEstimate::new(components.clone(), &fee_args(Some("0.15"), None)).unwrap();
+        assert_eq!(capped.max_fee_per_gas, U256::from(150_000_000u64));
+        assert!(FeeEstimate::new(components.clone(), &fee_args(Some("0.05"), None)).is_err());
+        assert!(FeeEstimate::new(components, &fee_args(None, Some("0.00001"))).is_err());
+    }
+}
@@ -0,0 +1,15 @@
+use std::path::Path;
+
+use eyre::Result;
+
+/// Writes `bytes` to `path`, creating its directory. Writes a `.tmp` file first and renames it,
+/// so a crash never leaves a truncated file behind.
+pub(crate) fn write_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp_path = path.with_extension("tmp");
+    std::fs::write(&tmp_path, bytes)?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
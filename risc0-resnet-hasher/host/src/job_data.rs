@@ -0,0 +1,100 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use eyre::Result;
+use methods::resnet::ResNetConfig;
+use methods::{JobBinding, JobInputs, Preprocessing};
+use serde::{Deserialize, Serialize};
+
+use crate::model::{read_model_file, synthetic_model};
+use crate::preprocess::{load_image_batch, CIFAR10_MEAN, CIFAR10_STD};
+
+/// What a client publishes under `jobDataCID`: the guest inputs minus the job binding, which
+/// only exists once the job is on-chain and a provider is chosen.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobData {
+    pub model_weights_data: Vec<u8>,
+    pub image_batch_data: Vec<u8>,
+    pub preprocessing: Preprocessing,
+    pub sample_size: u32,
+}
+
+impl JobData {
+    pub fn into_inputs(self, binding: JobBinding) -> JobInputs {
+        JobInputs {
+            binding,
+            image_batch_data: self.image_batch_data,
+            model_weights_data: self.model_weights_data,
+            preprocessing: self.preprocessing,
+            sample_size: self.sample_size,
+        }
+    }
+
+    /// bincode, the same encoding as receipts on disk.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        Ok(bincode::serialize(self)?)
+    }
+
+    /// Decodes the bincode blob stored under a job's CID.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        bincode::deserialize(bytes).map_err(|e| eyre::eyre!("Job data is not a bincode JobData: {}", e))
+    }
+}
+
+/// Builds [JobData] from a model file and an image directory, as a client publishes it.
+#[derive(Args, Debug, Clone)]
+pub struct JobInputArgs {
+    /// Model file to run; defaults to synthetic ResNet-8 weights.
+    #[arg(long, env = "MODEL_FILE")]
+    model: Option<PathBuf>,
+    /// Directory of PNG/JPEG images (or preprocessed `.bin` tensors) to classify; defaults to
+    /// one flat placeholder image.
+    #[arg(long, env = "IMAGE_DIR")]
+    images: Option<PathBuf>,
+    /// Shorter image side after resizing, before the center crop to the model's input size.
+    #[arg(long, default_value_t = 32)]
+    resize: u32,
+    /// Per-channel mean of pixel values in [0, 1].
+    #[arg(long, value_delimiter = ',', default_values_t = CIFAR10_MEAN)]
+    mean: Vec<f32>,
+    /// Per-channel standard deviation of pixel values in [0, 1].
+    #[arg(long, value_delimiter = ',', default_values_t = CIFAR10_STD)]
+    std: Vec<f32>,
+    /// Normalized value of one int8 input step.
+    #[arg(long, default_value_t = 1.0 / 32.0)]
+    input_scale: f32,
+    /// Prove only this many images, picked inside the guest from the job seed; 0 proves the
+    /// whole batch.
+    #[arg(long, default_value_t = 0)]
+    sample_size: u32,
+}
+
+impl JobInputArgs {
+    /// Loads the model and preprocesses the images for its input shape, recording how.
+    pub fn job_data(&self) -> Result<JobData> {
+        let (config, model_weights_data) = match &self.model {
+            Some(path) => read_model_file(path)?,
+            None => {
+                let config = ResNetConfig::resnet8();
+                let weights = synthetic_model(&config);
+                (config, weights)
+            }
+        };
+        if self.mean.len() != config.in_channels || self.std.len() != config.in_channels {
+            eyre::bail!("--mean and --std need {} values, one per input channel.", config.in_channels);
+        }
+        let preprocessing = Preprocessing {
+            resize: self.resize,
+            crop_height: config.height as u32,
+            crop_width: config.width as u32,
+            mean: self.mean.clone(),
+            std: self.std.clone(),
+            input_scale: self.input_scale,
+        };
+        let image_batch_data = match &self.images {
+            Some(dir) => load_image_batch(dir, &preprocessing)?,
+            None => vec![32u8; config.image_len()],
+        };
+        Ok(JobData { model_weights_data, image_batch_data, preprocessing, sample_size: self.sample_size })
+    }
+}
@@ -0,0 +1,55 @@
+use eyre::Result;
+use risc0_zkvm::sha::{Digest, Digestible};
+use risc0_zkvm::{Receipt, ReceiptClaim, VerifierContext};
+use sha2::{Digest as _, Sha256};
+
+/// Journal digest in the form `IRiscZeroVerifier.verify` expects: `sha256(journal)`.
+///
+/// This is what `submitProofAndClaim` must receive as `_journalHash`; the verifier rebuilds the
+/// `ReceiptClaim` from it, so any other hash (e.g. keccak256) fails verification on-chain.
+pub fn journal_digest(journal: &[u8]) -> [u8; 32] {
+    Sha256::digest(journal).into()
+}
+
+/// Rebuilds the claim the on-chain verifier will check and verifies `receipt` against it locally.
+///
+/// Returns the journal digest to submit. Failing here costs nothing, whereas the same mismatch on
+/// chain is a `ZKProofVerificationFailed` revert that still burns gas.
+pub fn preflight_verify(receipt: &Receipt, image_id: impl Into<Digest>) -> Result<[u8; 32]> {
+    let image_id = image_id.into();
+    let expected_claim = ReceiptClaim::ok(image_id, receipt.journal.bytes.clone());
+    let receipt_claim = receipt
+        .claim()
+        .map_err(|e| eyre::eyre!("Receipt has no usable claim: {:?}", e))?;
+    if receipt_claim.digest() != expected_claim.digest() {
+        eyre::bail!(
+            "Receipt claim does not match ReceiptClaim::ok(image_id = {}, journal). The job's methodId or journal differs from what was proven.",
+            image_id
+        );
+    }
+    receipt
+        .verify_integrity_with_context(&VerifierContext::default())
+        .map_err(|e| eyre::eyre!("Local receipt verification failed: {:?}", e))?;
+    Ok(journal_digest(&receipt.journal.bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn journal_digest_is_sha256() {
+        assert_eq!(
+            hex::encode(journal_digest(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn journal_digest_matches_claim_output() {
+        let journal = b"gpu-market".to_vec();
+        let claim = ReceiptClaim::ok(Digest::ZERO, journal.clone());
+        let output = claim.output.value().unwrap().unwrap();
+        assert_eq!(output.journal.digest().as_bytes(), journal_digest(&journal));
+    }
+}
@@ -0,0 +1,173 @@
+use std::path::Path;
+
+use eyre::Result;
+use methods::resnet::ResNetConfig;
+use methods::weights::{ModelFile, ModelWriter};
+use safetensors::{Dtype, SafeTensors};
+
+/// Reads a model file from disk and checks its header before it is handed to the guest.
+pub fn read_model_file(path: &Path) -> Result<(ResNetConfig, Vec<u8>)> {
+    let bytes = std::fs::read(path).map_err(|e| eyre::eyre!("Cannot read model file {}: {}", path.display(), e))?;
+    let config = ModelFile::parse(&bytes)
+        .map_err(|e| eyre::eyre!("{} is not a valid model file: {:?}", path.display(), e))?
+        .config;
+    Ok((config, bytes))
+}
+
+/// Placeholder ResNet weights drawn from a fixed xorshift stream, for runs without a checkpoint.
+pub fn synthetic_model(config: &ResNetConfig) -> Vec<u8> {
+    let mut state: u32 = 0x2545_f491;
+    let mut next_weight = move || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        (state % 15) as i8 - 7
+    };
+    let mut writer = ModelWriter::new(config.clone());
+    while let Some(shape) = writer.next_conv_shape() {
+        let taps = shape.out_channels * shape.in_channels * shape.kernel * shape.kernel;
+        let weights: Vec<i8> = (0..taps).map(|_| next_weight()).collect();
+        writer.conv(&weights, &vec![0; shape.out_channels], 1, 6).expect("synthetic conv matches its shape");
+    }
+    let fc_weights: Vec<i8> = (0..config.num_classes * config.feature_channels()).map(|_| next_weight()).collect();
+    writer.finish(&fc_weights, &vec![0; config.num_classes]).expect("synthetic classifier matches its shape")
+}
+
+/// Converts a quantized safetensors checkpoint into a model file.
+///
+/// Tensor names follow torchvision's ResNet: `stem`, `layer{stage}.{block}.conv1`, `.conv2`,
+/// `.downsample` and `fc`. Each convolution provides `<name>.weight` (I8, `[out, in, k, k]`),
+/// `<name>.bias` (I32, `[out]`) and `<name>.requant` (I32, `[multiplier, shift]`); the classifier
+/// provides `fc.weight` (I8, `[classes, features]`) and `fc.bias` (I32). Channel counts and depth
+/// are read from the tensor shapes; the input resolution is not stored in the checkpoint.
+pub fn import_safetensors(path: &Path, height: usize, width: usize) -> Result<Vec<u8>> {
+    let bytes = std::fs::read(path).map_err(|e| eyre::eyre!("Cannot read {}: {}", path.display(), e))?;
+    let tensors = SafeTensors::deserialize(&bytes).map_err(|e| eyre::eyre!("{} is not a safetensors file: {}", path.display(), e))?;
+
+    let stem_shape = tensor_shape(&tensors, "stem.weight", 4)?;
+    let mut stage_channels = Vec::new();
+    loop {
+        let name = format!("layer{}.0.conv1.weight", stage_channels.len() + 1);
+        if tensors.tensor(&name).is_err() {
+            break;
+        }
+        stage_channels.push(tensor_shape(&tensors, &name, 4)?[0]);
+    }
+    let mut blocks_per_stage = 0;
+    while tensors.tensor(&format!("layer1.{}.conv1.weight", blocks_per_stage)).is_ok() {
+        blocks_per_stage += 1;
+    }
+    let config = ResNetConfig {
+        in_channels: stem_shape[1],
+        height,
+        width,
+        stem_channels: stem_shape[0],
+        stage_channels,
+        blocks_per_stage,
+        num_classes: tensor_shape(&tensors, "fc.weight", 2)?[0],
+    };
+    println!("Importing {} as {:?}", path.display(), config);
+
+    let mut writer = ModelWriter::new(config.clone());
+    write_conv(&tensors, &mut writer, "stem")?;
+    for stage in 1..=config.stage_channels.len() {
+        for block in 0..config.blocks_per_stage {
+            write_conv(&tensors, &mut writer, &format!("layer{}.{}.conv1", stage, block))?;
+            write_conv(&tensors, &mut writer, &format!("layer{}.{}.conv2", stage, block))?;
+            if writer.next_conv_shape().is_some_and(|shape| shape.kernel == 1) {
+                write_conv(&tensors, &mut writer, &format!("layer{}.{}.downsample", stage, block))?;
+            }
+        }
+    }
+    writer
+        .finish(&i8_tensor(&tensors, "fc.weight")?, &i32_tensor(&tensors, "fc.bias")?)
+        .map_err(|e| eyre::eyre!("fc: {:?}", e))
+}
+
+fn write_conv(tensors: &SafeTensors, writer: &mut ModelWriter, name: &str) -> Result<()> {
+    let requant = i32_tensor(tensors, &format!("{}.requant", name))?;
+    let [multiplier, shift] = requant[..] else {
+        eyre::bail!("{}.requant must hold [multiplier, shift], found {} values", name, requant.len());
+    };
+    writer
+        .conv(
+            &i8_tensor(tensors, &format!("{}.weight", name))?,
+            &i32_tensor(tensors, &format!("{}.bias", name))?,
+            multiplier,
+            shift as u32,
+        )
+        .map_err(|e| eyre::eyre!("{}: {:?}", name, e))?;
+    Ok(())
+}
+
+fn tensor_shape(tensors: &SafeTensors, name: &str, rank: usize) -> Result<Vec<usize>> {
+    let view = tensors.tensor(name).map_err(|e| eyre::eyre!("Missing tensor {}: {}", name, e))?;
+    if view.shape().len() != rank {
+        eyre::bail!("Tensor {} has shape {:?}; expected {} dimensions.", name, view.shape(), rank);
+    }
+    Ok(view.shape().to_vec())
+}
+
+fn i8_tensor(tensors: &SafeTensors, name: &str) -> Result<Vec<i8>> {
+    let view = tensors.tensor(name).map_err(|e| eyre::eyre!("Missing tensor {}: {}", name, e))?;
+    if view.dtype() != Dtype::I8 {
+        eyre::bail!("Tensor {} is {:?}; expected I8. Quantize the checkpoint before importing.", name, view.dtype());
+    }
+    Ok(view.data().iter().map(|&b| b as i8).collect())
+}
+
+fn i32_tensor(tensors: &SafeTensors, name: &str) -> Result<Vec<i32>> {
+    let view = tensors.tensor(name).map_err(|e| eyre::eyre!("Missing tensor {}: {}", name, e))?;
+    if view.dtype() != Dtype::I32 {
+        eyre::bail!("Tensor {} is {:?}; expected I32.", name, view.dtype());
+    }
+    Ok(view.data().as_chunks::<4>().0.iter().map(|b| i32::from_le_bytes(*b)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use safetensors::tensor::TensorView;
+
+    #[test]
+    fn imports_torchvision_style_checkpoint() {
+        let config = ResNetConfig {
+            in_channels: 1,
+            height: 4,
+            width: 4,
+            stem_channels: 2,
+            stage_channels: vec![2, 4],
+            blocks_per_stage: 1,
+            num_classes: 3,
+        };
+        let names = ["stem", "layer1.0.conv1", "layer1.0.conv2", "layer2.0.conv1", "layer2.0.conv2", "layer2.0.downsample"];
+        let mut owned: Vec<(String, Dtype, Vec<usize>, Vec<u8>)> = Vec::new();
+        for (name, shape) in names.iter().zip(config.conv_shapes()) {
+            let dims = vec![shape.out_channels, shape.in_channels, shape.kernel, shape.kernel];
+            owned.push((format!("{}.weight", name), Dtype::I8, dims.clone(), vec![1; dims.iter().product()]));
+            owned.push((format!("{}.bias", name), Dtype::I32, vec![shape.out_channels], vec![0; shape.out_channels * 4]));
+            let requant: Vec<u8> = [1i32, 3].iter().flat_map(|v| v.to_le_bytes()).collect();
+            owned.push((format!("{}.requant", name), Dtype::I32, vec![2], requant));
+        }
+        owned.push(("fc.weight".into(), Dtype::I8, vec![3, 4], vec![1; 12]));
+        owned.push(("fc.bias".into(), Dtype::I32, vec![3], vec![0; 12]));
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("model.safetensors");
+        let import = |owned: &[(String, Dtype, Vec<usize>, Vec<u8>)]| {
+            let views: Vec<(String, TensorView)> = owned
+                .iter()
+                .map(|(name, dtype, shape, data)| (name.clone(), TensorView::new(*dtype, shape.clone(), data).unwrap()))
+                .collect();
+            safetensors::serialize_to_file(views, None, &path).unwrap();
+            import_safetensors(&path, 4, 4)
+        };
+
+        let model = import(&owned).unwrap();
+        assert_eq!(ModelFile::parse(&model).unwrap().config, config);
+
+        // A flattened stem is refused by name instead of panicking on a missing dimension.
+        owned[0].2 = vec![2 * 9];
+        let err = import(&owned).unwrap_err().to_string();
+        assert!(err.contains("stem.weight"), "{}", err);
+    }
+}
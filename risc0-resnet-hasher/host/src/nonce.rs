@@ -0,0 +1,349 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::utils::keccak256;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::config::SignerClient;
+use crate::fs::write_atomic;
+use crate::tx::TxArgs;
+
+/// A transaction sent but not yet mined, with every signed version sent for its nonce.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingTx {
+    pub what: String,
+    pub nonce: U256,
+    /// The latest version, unsigned; replacements bump its fees.
+    pub request: TypedTransaction,
+    /// Signed versions, oldest first; any of them may be the one that gets mined.
+    pub raw: Vec<Bytes>,
+    pub hashes: Vec<H256>,
+    /// Unix time the latest version was sent.
+    pub sent_at: u64,
+}
+
+/// Nonce managers by signer address, shared by everything one process sends.
+#[derive(Debug, Clone, Default)]
+pub struct NonceManagers(Arc<Mutex<HashMap<Address, Arc<NonceManager>>>>);
+
+impl NonceManagers {
+    /// The manager for `client`'s address, loading and rebroadcasting its persisted pending
+    /// transactions the first time.
+    pub async fn get(&self, client: &SignerClient, tx_args: &TxArgs) -> Result<Arc<NonceManager>> {
+        let address = client.address();
+        if let Some(manager) = self.0.lock().expect("nonce managers poisoned").get(&address) {
+            return Ok(manager.clone());
+        }
+        let opened = Arc::new(NonceManager::open(client, &tx_args.pending_tx_dir).await?);
+        Ok(self.0.lock().expect("nonce managers poisoned").entry(address).or_insert(opened).clone())
+    }
+}
+
+#[derive(Debug, Default)]
+struct NonceState {
+    /// Next nonce to hand out, once one has been.
+    next: Option<U256>,
+    pending: BTreeMap<U256, PendingTx>,
+    /// Nonces a `send` is currently waiting on.
+    watching: HashSet<U256>,
+}
+
+/// Hands out nonces locally so overlapping transactions from one signer do not collide, and
+/// tracks them in `<pending-tx-dir>/<address>.json` until they are mined so a restart neither
+/// reuses nor skips a nonce.
+#[derive(Debug)]
+pub struct NonceManager {
+    address: Address,
+    path: PathBuf,
+    state: Mutex<NonceState>,
+}
+
+impl NonceManager {
+    async fn open(client: &SignerClient, pending_tx_dir: &Path) -> Result<Self> {
+        let address = client.address();
+        let path = pending_tx_dir.join(format!("{:?}.json", address));
+        let pending: BTreeMap<U256, PendingTx> = match std::fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| eyre::eyre!("Corrupt pending transactions {}: {}", path.display(), e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let manager = Self { address, path, state: Mutex::new(NonceState { next: None, pending, watching: HashSet::new() }) };
+        manager.prune(client).await?;
+
+        // A node may have dropped them while we were down; without them later nonces would stall.
+        let unmined: Vec<PendingTx> = manager.lock().pending.values().cloned().collect();
+        for tx in unmined {
+            println!("Rebroadcasting pending {} (nonce {}, tx {:?})...", tx.what, tx.nonce, tx.hashes.last().copied().unwrap_or_default());
+            if let Some(raw) = tx.raw.last() {
+                if let Err(e) = client.provider().send_raw_transaction(raw.clone()).await {
+                    println!("Rebroadcast of nonce {} not accepted: {}", tx.nonce, e);
+                }
+            }
+        }
+        Ok(manager)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, NonceState> {
+        self.state.lock().expect("nonce state poisoned")
+    }
+
+    /// Forgets pending transactions whose nonce the chain has already used.
+    async fn prune(&self, client: &SignerClient) -> Result<()> {
+        let mined = client.get_transaction_count(self.address, Some(BlockNumber::Latest.into())).await?;
+        let mut state = self.lock();
+        let before = state.pending.len();
+        state.pending.retain(|nonce, _| *nonce >= mined);
+        if state.pending.len() != before {
+            self.persist(&state)?;
+        }
+        Ok(())
+    }
+
+    /// The next unused nonce: past both the chain's pending count and every tracked transaction.
+    /// Tracked transactions no `send` is watching any more are picked up again first, so one that
+    /// timed out cannot silently stall every nonce handed out after it.
+    async fn reserve(&self, client: &SignerClient, tx_args: &TxArgs) -> Result<U256> {
+        self.prune(client).await?;
+        self.recheck_unwatched(client, tx_args).await?;
+        let on_chain = client.get_transaction_count(self.address, Some(BlockNumber::Pending.into())).await?;
+        let mut state = self.lock();
+        let tracked = state.pending.keys().next_back().map(|nonce| nonce + 1).unwrap_or_default();
+        let nonce = on_chain.max(tracked).max(state.next.unwrap_or_default());
+        state.next = Some(nonce + 1);
+        Ok(nonce)
+    }
+
+    /// Rebroadcasts unmined transactions left behind by a timed-out `send` or a restart, replacing
+    /// those pending for `--replace-after-secs` with bumped fees. Mined ones were already pruned.
+    async fn recheck_unwatched(&self, client: &SignerClient, tx_args: &TxArgs) -> Result<()> {
+        let unwatched: Vec<PendingTx> = {
+            let state = self.lock();
+            state.pending.values().filter(|tx| !state.watching.contains(&tx.nonce)).cloned().collect()
+        };
+        for mut tx in unwatched {
+            if unix_now().saturating_sub(tx.sent_at) >= tx_args.replace_after_secs {
+                self.replace(client, tx_args, &mut tx).await?;
+            } else if let Some(raw) = tx.raw.last() {
+                if let Err(e) = client.provider().send_raw_transaction(raw.clone()).await {
+                    println!("Rebroadcast of {} (nonce {}) not accepted: {}", tx.what, tx.nonce, e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Signs and sends `request` with the next nonce and waits for it to be mined, replacing it
+    /// with bumped fees each time it stays pending for `--replace-after-secs`.
+    pub async fn send(&self, client: &SignerClient, tx_args: &TxArgs, what: &str, mut request: TypedTransaction) -> Result<TransactionReceipt> {
+        let nonce = self.reserve(client, tx_args).await?;
+        request.set_nonce(nonce).set_from(self.address).set_chain_id(client.signer().chain_id());
+        if request.gas_price().is_none() {
+            request.set_gas_price(client.get_gas_price().await?);
+        }
+
+        let (raw, hash) = match sign(client, &request).await {
+            Ok(signed) => signed,
+            Err(e) => return Err(self.release(nonce, e)),
+        };
+        if let Err(e) = client.provider().send_raw_transaction(raw.clone()).await {
+            return Err(self.release(nonce, eyre::eyre!("Sending {} failed: {}", what, e)));
+        }
+        println!("Sent {} with nonce {}. Tx: {:?}", what, nonce, hash);
+        let mut pending = PendingTx { what: what.to_string(), nonce, request, raw: vec![raw], hashes: vec![hash], sent_at: unix_now() };
+        self.lock().watching.insert(nonce);
+        let result = match self.track(&pending) {
+            Ok(()) => self.watch(client, tx_args, &mut pending).await,
+            Err(e) => Err(e),
+        };
+        self.lock().watching.remove(&nonce);
+        result
+    }
+
+    /// Polls for a receipt of any version of `pending` until `--wait-timeout-secs`. RPC errors are
+    /// retried until then; on timeout the transaction stays tracked for the next `reserve`.
+    async fn watch(&self, client: &SignerClient, tx_args: &TxArgs, pending: &mut PendingTx) -> Result<TransactionReceipt> {
+        let nonce = pending.nonce;
+        let deadline = Instant::now() + Duration::from_secs(tx_args.wait.wait_timeout_secs);
+        let mut last_sent = Instant::now();
+        loop {
+            match self.poll(client, pending).await {
+                Ok(Poll::Mined(receipt)) => {
+                    self.untrack(nonce)?;
+                    return Ok(*receipt);
+                }
+                Ok(Poll::NonceTaken) => {
+                    self.untrack(nonce)?;
+                    eyre::bail!("Nonce {} of {} was used by another transaction; {:?} will never be mined.", nonce, pending.what, pending.hashes);
+                }
+                Ok(Poll::Pending) => {}
+                Err(e) => println!("⚠️ RPC error while waiting for {} (nonce {}): {}; retrying.", pending.what, nonce, e),
+            }
+            if Instant::now() >= deadline {
+                eyre::bail!(
+                    "{} (nonce {}) still pending after {}s. Tx: {:?}; it stays tracked in {} and is rebroadcast before the next send.",
+                    pending.what,
+                    nonce,
+                    tx_args.wait.wait_timeout_secs,
+                    pending.hashes.last().copied().unwrap_or_default(),
+                    self.path.display()
+                );
+            }
+            if last_sent.elapsed() >= Duration::from_secs(tx_args.replace_after_secs) {
+                if let Err(e) = self.replace(client, tx_args, pending).await {
+                    println!("⚠️ Replacement of {} failed: {}", pending.what, e);
+                }
+                last_sent = Instant::now();
+            }
+            tokio::time::sleep(Duration::from_millis(tx_args.wait.poll_interval_ms)).await;
+        }
+    }
+
+    async fn poll(&self, client: &SignerClient, pending: &PendingTx) -> Result<Poll> {
+        if let Some(receipt) = find_receipt(client, pending).await? {
+            return Ok(Poll::Mined(Box::new(receipt)));
+        }
+        if client.get_transaction_count(self.address, Some(BlockNumber::Latest.into())).await? > pending.nonce {
+            // One of ours may have been mined between the two reads.
+            return Ok(match find_receipt(client, pending).await? {
+                Some(receipt) => Poll::Mined(Box::new(receipt)),
+                None => Poll::NonceTaken,
+            });
+        }
+        Ok(Poll::Pending)
+    }
+
+    /// Re-signs `pending` with fees bumped by `--gas-bump-pct` and sends it. Earlier versions stay
+    /// valid until one of them is mined.
+    async fn replace(&self, client: &SignerClient, tx_args: &TxArgs, pending: &mut PendingTx) -> Result<()> {
+        if !bump_fees(&mut pending.request, tx_args.gas_bump_pct, tx_args.fees.max_fee_per_gas()?) {
+            println!("⚠️ {} stuck for {}s but its fees are already at --max-fee-gwei; still waiting.", pending.what, tx_args.replace_after_secs);
+            return Ok(());
+        }
+        let (raw, hash) = sign(client, &pending.request).await?;
+        match client.provider().send_raw_transaction(raw.clone()).await {
+            Ok(_) => {
+                println!(
+                    "⚠️ {} stuck for {}s; replaced with gas price {} (+{}%). Tx: {:?}",
+                    pending.what,
+                    tx_args.replace_after_secs,
+                    pending.request.gas_price().unwrap_or_default(),
+                    tx_args.gas_bump_pct,
+                    hash
+                );
+                pending.raw.push(raw);
+                pending.hashes.push(hash);
+                pending.sent_at = unix_now();
+                self.track(pending)?;
+            }
+            Err(e) => println!("Replacement of {} not accepted: {}", pending.what, e),
+        }
+        Ok(())
+    }
+
+    /// Gives `nonce` back after a send that never reached the node, so the next one reuses it.
+    fn release(&self, nonce: U256, err: eyre::Report) -> eyre::Report {
+        let mut state = self.lock();
+        if state.next == Some(nonce + 1) {
+            state.next = Some(nonce);
+        } else {
+            // Later nonces are already out; re-read the chain next time rather than leave a gap.
+            state.next = None;
+        }
+        err
+    }
+
+    fn track(&self, tx: &PendingTx) -> Result<()> {
+        let mut state = self.lock();
+        state.pending.insert(tx.nonce, tx.clone());
+        self.persist(&state)
+    }
+
+    fn untrack(&self, nonce: U256) -> Result<()> {
+        let mut state = self.lock();
+        state.pending.remove(&nonce);
+        self.persist(&state)
+    }
+
+    fn persist(&self, state: &NonceState) -> Result<()> {
+        write_atomic(&self.path, serde_json::to_string_pretty(&state.pending)?.as_bytes())
+    }
+}
+
+/// What one poll of a pending transaction found.
+enum Poll {
+    Mined(Box<TransactionReceipt>),
+    Pending,
+    /// The nonce was used by a transaction this manager never sent.
+    NonceTaken,
+}
+
+async fn find_receipt(client: &SignerClient, pending: &PendingTx) -> Result<Option<TransactionReceipt>> {
+    for hash in pending.hashes.iter().rev() {
+        if let Some(receipt) = client.get_transaction_receipt(*hash).await? {
+            return Ok(Some(receipt));
+        }
+    }
+    Ok(None)
+}
+
+async fn sign(client: &SignerClient, request: &TypedTransaction) -> Result<(Bytes, H256)> {
+    let signature = client.signer().sign_transaction(request).await?;
+    let raw = request.rlp_signed(&signature);
+    let hash = H256::from(keccak256(&raw));
+    Ok((raw, hash))
+}
+
+/// Raises every fee of `request` by `pct` percent, rounding up, but not past `cap`. Nodes only
+/// accept a replacement that raises both EIP-1559 fees. Returns false if the fees are already at
+/// the cap.
+pub fn bump_fees(request: &mut TypedTransaction, pct: u64, cap: Option<U256>) -> bool {
+    let bump = |fee: U256| {
+        let bumped = (fee * (100 + pct) + 99) / 100;
+        cap.map_or(bumped, |cap| bumped.min(cap).max(fee))
+    };
+    match request {
+        TypedTransaction::Eip1559(inner) => {
+            let before = inner.max_fee_per_gas;
+            inner.max_fee_per_gas = inner.max_fee_per_gas.map(bump);
+            inner.max_priority_fee_per_gas = inner.max_priority_fee_per_gas.map(|fee| bump(fee).min(inner.max_fee_per_gas.unwrap_or(fee)));
+            inner.max_fee_per_gas != before
+        }
+        _ => match request.gas_price() {
+            Some(gas_price) if bump(gas_price) > gas_price => {
+                request.set_gas_price(bump(gas_price));
+                true
+            }
+            _ => false,
+        },
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bump_fees_raises_both_eip1559_fees_and_legacy_gas_price() {
+        let mut eip1559: TypedTransaction = Eip1559TransactionRequest::new().max_fee_per_gas(100u64).max_priority_fee_per_gas(3u64).into();
+        assert!(bump_fees(&mut eip1559, 10, None));
+        let TypedTransaction::Eip1559(inner) = &eip1559 else { panic!("type changed") };
+        assert_eq!((inner.max_fee_per_gas, inner.max_priority_fee_per_gas), (Some(110u64.into()), Some(4u64.into())));
+
+        let mut legacy: TypedTransaction = TransactionRequest::new().gas_price(1_000u64).into();
+        assert!(bump_fees(&mut legacy, 20, None));
+        assert_eq!(legacy.gas_price(), Some(1_200u64.into()));
+
+        assert!(bump_fees(&mut legacy, 20, Some(1_300u64.into())));
+        assert_eq!(legacy.gas_price(), Some(1_300u64.into()));
+        assert!(!bump_fees(&mut legacy, 20, Some(1_300u64.into())));
+    }
+}
@@ -0,0 +1,119 @@
+use std::path::{Path, PathBuf};
+
+use eyre::Result;
+use image::imageops::FilterType;
+use methods::Preprocessing;
+
+/// CIFAR-10 training-set statistics, the default for the ResNet-8 guest model.
+pub const CIFAR10_MEAN: [f32; 3] = [0.4914, 0.4822, 0.4465];
+pub const CIFAR10_STD: [f32; 3] = [0.2470, 0.2435, 0.2616];
+
+/// Quantizes one decoded pixel of `channel` the way `Preprocessing` documents.
+pub fn normalize_pixel(preprocessing: &Preprocessing, channel: usize, pixel: u8) -> i8 {
+    let normalized = (pixel as f32 / 255.0 - preprocessing.mean[channel]) / preprocessing.std[channel];
+    (normalized / preprocessing.input_scale).round().clamp(i8::MIN as f32, i8::MAX as f32) as i8
+}
+
+/// Resizes, center-crops and normalizes one image into int8 CHW. One input channel takes the
+/// image as grayscale, three as RGB; other models cannot be fed decoded images.
+pub fn preprocess_image(image: &image::DynamicImage, preprocessing: &Preprocessing) -> Result<Vec<u8>> {
+    let channels = preprocessing.mean.len();
+    let (width, height) = (image.width(), image.height());
+    let scale = preprocessing.resize as f32 / width.min(height) as f32;
+    let resized_width = ((width as f32 * scale).round() as u32).max(preprocessing.crop_width);
+    let resized_height = ((height as f32 * scale).round() as u32).max(preprocessing.crop_height);
+    let cropped = image
+        .resize_exact(resized_width, resized_height, FilterType::Triangle)
+        .crop_imm(
+            (resized_width - preprocessing.crop_width) / 2,
+            (resized_height - preprocessing.crop_height) / 2,
+            preprocessing.crop_width,
+            preprocessing.crop_height,
+        );
+    let interleaved = match channels {
+        1 => cropped.to_luma8().into_raw(),
+        3 => cropped.to_rgb8().into_raw(),
+        _ => eyre::bail!("Images can only be decoded for 1 (grayscale) or 3 (RGB) input channels, not {}; pass preprocessed .bin tensors instead.", channels),
+    };
+
+    let mut chw = Vec::with_capacity(interleaved.len());
+    for channel in 0..channels {
+        chw.extend(interleaved.iter().skip(channel).step_by(channels).map(|&pixel| normalize_pixel(preprocessing, channel, pixel) as u8));
+    }
+    Ok(chw)
+}
+
+/// Builds an int8 NCHW batch from every image in `dir`, in file name order.
+///
+/// PNG and JPEG files go through [preprocess_image]. `.bin` files are taken as already
+/// preprocessed int8 CHW tensors and must be exactly one image long.
+pub fn load_image_batch(dir: &Path, preprocessing: &Preprocessing) -> Result<Vec<u8>> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|e| eyre::eyre!("Cannot read image directory {}: {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .collect();
+    paths.sort();
+
+    let image_len = preprocessing.mean.len() * (preprocessing.crop_height * preprocessing.crop_width) as usize;
+    let mut batch = Vec::new();
+    for path in &paths {
+        let extension = path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("png" | "jpg" | "jpeg") => {
+                let image = image::open(path).map_err(|e| eyre::eyre!("Cannot decode {}: {}", path.display(), e))?;
+                batch.extend(preprocess_image(&image, preprocessing)?);
+            }
+            Some("bin") => {
+                let tensor = std::fs::read(path)?;
+                if tensor.len() != image_len {
+                    eyre::bail!("{} holds {} bytes; a preprocessed image is {} bytes.", path.display(), tensor.len(), image_len);
+                }
+                batch.extend(tensor);
+            }
+            _ => println!("Skipping {} (not .png, .jpg, .jpeg or .bin)", path.display()),
+        }
+    }
+    if batch.is_empty() {
+        eyre::bail!("No images found in {}.", dir.display());
+    }
+    println!("Loaded {} images from {}", batch.len() / image_len, dir.display());
+    Ok(batch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cifar_preprocessing() -> Preprocessing {
+        Preprocessing {
+            resize: 32,
+            crop_height: 32,
+            crop_width: 32,
+            mean: CIFAR10_MEAN.to_vec(),
+            std: CIFAR10_STD.to_vec(),
+            input_scale: 1.0 / 32.0,
+        }
+    }
+
+    #[test]
+    fn resizes_crops_and_lays_out_chw() {
+        let preprocessing = cifar_preprocessing();
+        let image = image::DynamicImage::ImageRgb8(image::RgbImage::from_pixel(64, 48, image::Rgb([255, 0, 128])));
+        let chw = preprocess_image(&image, &preprocessing).unwrap();
+        assert_eq!(chw.len(), 3 * 32 * 32);
+        assert_eq!(chw[0] as i8, normalize_pixel(&preprocessing, 0, 255));
+        assert_eq!(chw[32 * 32] as i8, normalize_pixel(&preprocessing, 1, 0));
+        assert_eq!(chw[2 * 32 * 32] as i8, normalize_pixel(&preprocessing, 2, 128));
+        assert_eq!(normalize_pixel(&preprocessing, 1, 0), -63);
+
+        let grayscale = Preprocessing { mean: vec![0.5], std: vec![0.25], ..cifar_preprocessing() };
+        let luma = image.to_luma8().get_pixel(0, 0)[0];
+        let chw = preprocess_image(&image, &grayscale).unwrap();
+        assert_eq!(chw.len(), 32 * 32);
+        assert_eq!(chw[0] as i8, normalize_pixel(&grayscale, 0, luma));
+
+        let four_channels = Preprocessing { mean: vec![0.5; 4], std: vec![0.25; 4], ..cifar_preprocessing() };
+        assert!(preprocess_image(&image, &four_channels).is_err());
+    }
+}
@@ -0,0 +1,130 @@
+use clap::Args;
+use ethers::types::U256;
+use eyre::Result;
+
+use crate::config::parse_credits;
+use crate::contracts::format_credits;
+use crate::prover::ExecutionReport;
+
+/// What proving costs this provider, in GPUCredit. Amounts are decimal strings like `--reward`.
+#[derive(Args, Debug, Clone)]
+pub struct PricingArgs {
+    /// Cost of one million padded cycles (every segment rounded up to its power of two).
+    #[arg(long, env = "CREDITS_PER_MCYCLE", default_value = "0.01")]
+    credits_per_mcycle: String,
+    /// Fixed cost per segment, e.g. per-segment setup on the prover.
+    #[arg(long, env = "CREDITS_PER_SEGMENT", default_value = "0")]
+    credits_per_segment: String,
+    /// Fixed cost of the Groth16 wrapping, which does not depend on the cycle count.
+    #[arg(long, env = "GROTH16_CREDITS", default_value = "0.05")]
+    groth16_credits: String,
+    /// Fixed cost of the acceptJob and submitProofAndClaim transactions.
+    #[arg(long, env = "TX_COST_CREDITS", default_value = "0")]
+    tx_cost_credits: String,
+    /// Refuse jobs whose reward is less than the estimated cost plus this many percent.
+    #[arg(long, env = "MIN_MARGIN_PCT", default_value_t = 20)]
+    min_margin_pct: u64,
+}
+
+/// Per-unit costs in GPUCredit wei.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostModel {
+    pub per_mcycle: U256,
+    pub per_segment: U256,
+    pub groth16: U256,
+    pub tx_cost: U256,
+    pub min_margin_pct: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostEstimate {
+    pub cost: U256,
+    /// `cost` plus the margin; the least reward worth accepting.
+    pub min_reward: U256,
+}
+
+impl PricingArgs {
+    pub fn cost_model(&self) -> Result<CostModel> {
+        Ok(CostModel {
+            per_mcycle: parse_credits(&self.credits_per_mcycle)?,
+            per_segment: parse_credits(&self.credits_per_segment)?,
+            groth16: parse_credits(&self.groth16_credits)?,
+            tx_cost: parse_credits(&self.tx_cost_credits)?,
+            min_margin_pct: self.min_margin_pct,
+        })
+    }
+}
+
+impl CostModel {
+    pub fn estimate(&self, report: &ExecutionReport) -> CostEstimate {
+        let cycles = self.per_mcycle * U256::from(report.padded_cycles) / U256::from(1_000_000u64);
+        let segments = self.per_segment * U256::from(report.segments);
+        let cost = cycles + segments + self.groth16 + self.tx_cost;
+        let min_reward = cost * U256::from(100 + self.min_margin_pct) / U256::from(100u64);
+        CostEstimate { cost, min_reward }
+    }
+
+    /// Estimates `report` and explains why `reward` does not cover it, if it does not.
+    pub fn refuse_reason(&self, reward: U256, report: &ExecutionReport) -> Option<String> {
+        let estimate = self.estimate(report);
+        println!(
+            "Estimated proving cost: {} GPUCredit for {} padded cycles in {} segments; minimum reward with {}% margin: {}",
+            format_credits(estimate.cost),
+            report.padded_cycles,
+            report.segments,
+            self.min_margin_pct,
+            format_credits(estimate.min_reward)
+        );
+        (reward < estimate.min_reward).then(|| {
+            format!(
+                "reward {} is below estimated cost {} plus {}% margin",
+                format_credits(reward),
+                format_credits(estimate.cost),
+                self.min_margin_pct
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use methods::{JobBinding, JobOutputs, Preprocessing};
+
+    fn report(padded_cycles: u64, segments: usize) -> ExecutionReport {
+        ExecutionReport {
+            segments,
+            user_cycles: padded_cycles / 2,
+            padded_cycles,
+            execute_secs: 0.0,
+            outputs: JobOutputs {
+                binding: JobBinding { job_id: [0; 32], chain_id: 0, job_manager: [0; 20], client: [0; 20], seed: [0; 32] },
+                image_batch_hash: [0; 32],
+                model_weights_hash: [0; 32],
+                preprocessing: Preprocessing { resize: 0, crop_height: 0, crop_width: 0, mean: vec![], std: vec![], input_scale: 0.0 },
+                batch_size: 0,
+                sampled_indices: vec![],
+                num_classes: 0,
+                logits: vec![],
+            },
+        }
+    }
+
+    #[test]
+    fn estimate_applies_rates_and_margin() {
+        let model = CostModel {
+            per_mcycle: parse_credits("0.01").unwrap(),
+            per_segment: parse_credits("0.001").unwrap(),
+            groth16: parse_credits("0.05").unwrap(),
+            tx_cost: parse_credits("0.02").unwrap(),
+            min_margin_pct: 50,
+        };
+        // 4 segments of 2^20 cycles: 0.04194304 + 0.004 + 0.05 + 0.02.
+        let report = report(4 << 20, 4);
+        let estimate = model.estimate(&report);
+        assert_eq!(estimate.cost, parse_credits("0.11594304").unwrap());
+        assert_eq!(estimate.min_reward, parse_credits("0.17391456").unwrap());
+        assert!(model.refuse_reason(estimate.min_reward, &report).is_none());
+        assert!(model.refuse_reason(estimate.min_reward - 1, &report).is_some());
+    }
+}
@@ -0,0 +1,166 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use ethers::prelude::*;
+use eyre::Result;
+use risc0_zkvm::{ProveInfo, Receipt};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::fs::write_atomic;
+use crate::journal::journal_digest;
+use crate::seal::encode_router_seal;
+
+/// `SessionStats` of a proving run, which risc0 does not make serializable, plus wall time.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProvingStats {
+    pub segments: usize,
+    pub total_cycles: u64,
+    pub user_cycles: u64,
+    pub paging_cycles: u64,
+    pub reserved_cycles: u64,
+    /// Wall time of the succinct proof.
+    pub proving_secs: f64,
+    /// Wall time of the Groth16 wrapping; zero until it has run.
+    pub groth16_secs: f64,
+}
+
+impl ProvingStats {
+    pub fn new(info: &ProveInfo, elapsed: Duration) -> Self {
+        Self {
+            segments: info.stats.segments,
+            total_cycles: info.stats.total_cycles,
+            user_cycles: info.stats.user_cycles,
+            paging_cycles: info.stats.paging_cycles,
+            reserved_cycles: info.stats.reserved_cycles,
+            proving_secs: elapsed.as_secs_f64(),
+            groth16_secs: 0.0,
+        }
+    }
+}
+
+/// A succinct receipt between proving and Groth16 wrapping.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuccinctCheckpoint {
+    pub job_id: U256,
+    pub receipt: Receipt,
+    pub stats: ProvingStats,
+}
+
+/// A Groth16 proof ready for `submitProofAndClaim`, kept until the job is completed on-chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredProof {
+    pub job_id: U256,
+    pub receipt: Receipt,
+    pub stats: ProvingStats,
+    /// Selector-prefixed seal for the verifier router.
+    pub seal: Vec<u8>,
+    pub journal: Vec<u8>,
+    /// `sha256(journal)`, the `_journalHash` argument.
+    pub journal_hash: [u8; 32],
+    /// Last `submitProofAndClaim` transaction sent for this proof.
+    pub submit_tx: Option<H256>,
+    /// Set once the job is `Completed` on-chain; the proof is then kept only for reference.
+    pub completed: bool,
+}
+
+impl StoredProof {
+    pub fn new(job_id: U256, receipt: Receipt, stats: ProvingStats) -> Result<Self> {
+        let (seal, _) = encode_router_seal(&receipt)?;
+        let journal = receipt.journal.bytes.clone();
+        Ok(Self { job_id, journal_hash: journal_digest(&journal), receipt, stats, seal, journal, submit_tx: None, completed: false })
+    }
+}
+
+/// Proofs on disk as `<dir>/job-<id>.proof` (bincode [StoredProof]), so a failed submission can
+/// be retried after a restart instead of proving again, and succinct checkpoints as
+/// `<dir>/job-<id>.succinct` (bincode [SuccinctCheckpoint]), so Groth16 wrapping can be retried
+/// or moved to another machine on its own.
+#[derive(Debug, Clone)]
+pub struct ProofStore {
+    dir: PathBuf,
+}
+
+impl ProofStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, job_id: U256, extension: &str) -> PathBuf {
+        self.dir.join(format!("job-{}.{}", job_id, extension))
+    }
+
+    pub fn put(&self, proof: &StoredProof) -> Result<()> {
+        write_atomic(&self.path(proof.job_id, "proof"), &bincode::serialize(proof)?)
+    }
+
+    pub fn get(&self, job_id: U256) -> Result<Option<StoredProof>> {
+        read_proof(&self.path(job_id, "proof"))
+    }
+
+    pub fn put_succinct(&self, checkpoint: &SuccinctCheckpoint) -> Result<()> {
+        write_atomic(&self.path(checkpoint.job_id, "succinct"), &bincode::serialize(checkpoint)?)
+    }
+
+    pub fn get_succinct(&self, job_id: U256) -> Result<Option<SuccinctCheckpoint>> {
+        read_proof(&self.path(job_id, "succinct"))
+    }
+
+    /// Stored proofs whose job is not known to be completed, in job ID order.
+    pub fn pending(&self) -> Result<Vec<StoredProof>> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut pending = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|extension| extension == "proof") {
+                if let Some(proof) = read_proof::<StoredProof>(&path)? {
+                    if !proof.completed {
+                        pending.push(proof);
+                    }
+                }
+            }
+        }
+        pending.sort_by_key(|proof| proof.job_id);
+        Ok(pending)
+    }
+}
+
+fn read_proof<T: DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    match std::fs::read(path) {
+        Ok(bytes) => Ok(Some(bincode::deserialize(&bytes).map_err(|e| eyre::eyre!("Corrupt proof {}: {}", path.display(), e))?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use risc0_zkvm::sha::Digest;
+    use risc0_zkvm::{FakeReceipt, InnerReceipt, ReceiptClaim};
+
+    #[test]
+    fn pending_skips_completed_proofs_and_checkpoints() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ProofStore::new(dir.path());
+        let journal = b"journal".to_vec();
+        let receipt = Receipt::new(InnerReceipt::Fake(FakeReceipt::new(ReceiptClaim::ok(Digest::ZERO, journal.clone()))), journal);
+        for job_id in [3u64, 1, 2] {
+            let mut proof = StoredProof::new(U256::from(job_id), receipt.clone(), ProvingStats::default()).unwrap();
+            proof.completed = job_id == 2;
+            store.put(&proof).unwrap();
+        }
+        store.put_succinct(&SuccinctCheckpoint { job_id: U256::from(5u64), receipt, stats: ProvingStats::default() }).unwrap();
+
+        let pending: Vec<U256> = store.pending().unwrap().iter().map(|proof| proof.job_id).collect();
+        assert_eq!(pending, [U256::from(1u64), U256::from(3u64)]);
+        assert_eq!(store.get(U256::from(3u64)).unwrap().unwrap().journal_hash, journal_digest(b"journal"));
+        assert!(store.get(U256::from(4u64)).unwrap().is_none());
+        assert!(store.get(U256::from(5u64)).unwrap().is_none());
+        assert!(store.get_succinct(U256::from(5u64)).unwrap().is_some());
+    }
+}
@@ -0,0 +1,134 @@
+use std::time::Instant;
+
+use ethers::types::U256;
+use eyre::Result;
+use methods::{JobInputs, JobOutputs, RISC0_RESNET_HASHER_ELF};
+use risc0_zkvm::serde::to_vec as risc0_to_vec;
+use risc0_zkvm::{default_executor, default_prover, ExecutorEnv, ProveInfo, ProverOpts, Receipt};
+
+use crate::proof_store::{ProofStore, ProvingStats, StoredProof, SuccinctCheckpoint};
+use crate::schedule::{check_deadline, ProvingEstimate, Scheduler};
+
+/// Cycle counts of an execute-only run; what proving the same inputs will cost.
+#[derive(Debug, Clone)]
+pub struct ExecutionReport {
+    pub segments: usize,
+    /// User cycles, without continuation overhead or power-of-two padding.
+    pub user_cycles: u64,
+    /// Cycles the prover actually pays for: every segment padded to `2^po2`.
+    pub padded_cycles: u64,
+    pub execute_secs: f64,
+    pub outputs: JobOutputs,
+}
+
+fn guest_env(zk_guest_inputs: &JobInputs) -> Result<ExecutorEnv<'static>> {
+    let serialized_zk_inputs = risc0_to_vec(zk_guest_inputs)?;
+    ExecutorEnv::builder().write_slice(&serialized_zk_inputs).build().map_err(|e| eyre::eyre!("Env build: {:?}", e))
+}
+
+/// Stage 0: runs the guest without proving, to learn the cycle count and outputs up front.
+pub fn execute_job(job_id: U256, zk_guest_inputs: &JobInputs) -> Result<ExecutionReport> {
+    println!("\nExecuting guest for Job ID {} (dry run, no proof)...", job_id);
+    let started = Instant::now();
+    let session = default_executor()
+        .execute(guest_env(zk_guest_inputs)?, RISC0_RESNET_HASHER_ELF)
+        .map_err(|e| eyre::eyre!("R0 execution: {:?}", e))?;
+    let outputs: JobOutputs = session.journal.decode().map_err(|e| eyre::eyre!("Journal decode failed: {:?}", e))?;
+    let report = ExecutionReport {
+        segments: session.segments.len(),
+        user_cycles: session.cycles(),
+        padded_cycles: session.segments.iter().map(|segment| 1u64 << segment.po2).sum(),
+        execute_secs: started.elapsed().as_secs_f64(),
+        outputs,
+    };
+    println!(
+        "Executed in {:.1}s: {} segments, {} user cycles, {} padded cycles.",
+        report.execute_secs, report.segments, report.user_cycles, report.padded_cycles
+    );
+    print_outputs(&report.outputs);
+    Ok(report)
+}
+
+/// Stage 1: proves the guest as a succinct receipt. Fast relative to Groth16 and enough for
+/// aggregation.
+pub fn prove_succinct(job_id: U256, zk_guest_inputs: &JobInputs) -> Result<SuccinctCheckpoint> {
+    println!("\nProvider generating succinct proof for Job ID: {}", job_id);
+    let started = Instant::now();
+    let prove_info_data: ProveInfo = default_prover()
+        .prove_with_opts(guest_env(zk_guest_inputs)?, RISC0_RESNET_HASHER_ELF, &ProverOpts::succinct())
+        .map_err(|e| eyre::eyre!("R0 proving (succinct): {:?}", e))?;
+    let stats = ProvingStats::new(&prove_info_data, started.elapsed());
+    println!(
+        "R0 succinct proof successful in {:.1}s: {} segments, {} total cycles ({} user).",
+        stats.proving_secs, stats.segments, stats.total_cycles, stats.user_cycles
+    );
+    let guest_outputs: JobOutputs = prove_info_data.receipt.journal.decode().map_err(|e| eyre::eyre!("Journal decode failed: {:?}", e))?;
+    print_outputs(&guest_outputs);
+    Ok(SuccinctCheckpoint { job_id, receipt: prove_info_data.receipt, stats })
+}
+
+/// Stage 2: wraps a succinct receipt in Groth16 so it can be verified on-chain. Needs no guest
+/// inputs, so it can run on another machine from a copied checkpoint.
+pub fn compress_to_groth16(checkpoint: &SuccinctCheckpoint) -> Result<StoredProof> {
+    println!("\nWrapping succinct receipt for Job ID {} in Groth16...", checkpoint.job_id);
+    let started = Instant::now();
+    let zk_full_receipt: Receipt = default_prover()
+        .compress(&ProverOpts::groth16(), &checkpoint.receipt)
+        .map_err(|e| eyre::eyre!("R0 Groth16 compression: {:?}", e))?;
+    let mut stats = checkpoint.stats.clone();
+    stats.groth16_secs = started.elapsed().as_secs_f64();
+    println!("Groth16 compression successful in {:.1}s.", stats.groth16_secs);
+    StoredProof::new(checkpoint.job_id, zk_full_receipt, stats)
+}
+
+/// The succinct checkpoint for `job_id`, proving and storing it unless one is already stored.
+pub fn succinct_checkpoint(job_id: U256, zk_guest_inputs: &JobInputs, proof_store: &ProofStore) -> Result<SuccinctCheckpoint> {
+    if let Some(checkpoint) = proof_store.get_succinct(job_id)? {
+        println!("Using stored succinct receipt for Job ID {}.", job_id);
+        return Ok(checkpoint);
+    }
+    let checkpoint = prove_succinct(job_id, zk_guest_inputs)?;
+    proof_store.put_succinct(&checkpoint)?;
+    Ok(checkpoint)
+}
+
+/// Runs stage 2 for a job due at `deadline`: gives up if wrapping will obviously finish too late,
+/// then stores the proof and adds its proving times to the scheduler's history.
+pub fn compress_for_job(checkpoint: &SuccinctCheckpoint, deadline: U256, proof_store: &ProofStore, scheduler: &mut Scheduler) -> Result<StoredProof> {
+    let groth16_secs = scheduler.estimate_cycles(checkpoint.stats.total_cycles).groth16_secs;
+    check_deadline(checkpoint.job_id, deadline, "Groth16 wrapping", groth16_secs)?;
+    let proof = compress_to_groth16(checkpoint)?;
+    proof_store.put(&proof)?;
+    scheduler.record(&proof.stats)?;
+    Ok(proof)
+}
+
+/// Runs stages 1 and 2 for a job due at `deadline`, resuming from a stored succinct receipt if a
+/// previous run got that far, and abandoning the job before a stage that `estimate`, from an
+/// execute run, says it cannot finish in time.
+pub fn prove_job(
+    job_id: U256,
+    zk_guest_inputs: &JobInputs,
+    deadline: U256,
+    estimate: &ProvingEstimate,
+    proof_store: &ProofStore,
+    scheduler: &mut Scheduler,
+) -> Result<StoredProof> {
+    check_deadline(job_id, deadline, "the succinct proof", estimate.total_secs())?;
+    let checkpoint = succinct_checkpoint(job_id, zk_guest_inputs, proof_store)?;
+    compress_for_job(&checkpoint, deadline, proof_store, scheduler)
+}
+
+fn print_outputs(guest_outputs: &JobOutputs) {
+    println!("\n--- Guest Public Outputs (from Risc0 Journal) ---");
+    println!("Bound Job ID:          0x{}", hex::encode(guest_outputs.binding.job_id));
+    println!("Bound Chain / Manager: {} / 0x{}", guest_outputs.binding.chain_id, hex::encode(guest_outputs.binding.job_manager));
+    println!("Bound Client:          0x{}", hex::encode(guest_outputs.binding.client));
+    println!("Seed:                  0x{}", hex::encode(guest_outputs.binding.seed));
+    println!("Image Batch Hash:      0x{}", hex::encode(guest_outputs.image_batch_hash));
+    println!("Model Weights Hash:    0x{}", hex::encode(guest_outputs.model_weights_hash));
+    println!("Preprocessing:         {:?}", guest_outputs.preprocessing);
+    println!("Sampled Images:        {:?} of {}", guest_outputs.sampled_indices, guest_outputs.batch_size);
+    println!("Logits ({} classes):   {:?}", guest_outputs.num_classes, guest_outputs.logits);
+    println!("Predicted Classes:     {:?}", guest_outputs.predicted_classes());
+}
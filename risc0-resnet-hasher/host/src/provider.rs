@@ -0,0 +1,473 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use clap::{Args, Subcommand};
+use ethers::prelude::*;
+use eyre::Result;
+
+use methods::{JobInputs, RISC0_RESNET_HASHER_ID};
+use risc0_zkvm::sha::Digest;
+
+use crate::aggregation::ProofAggregator;
+use crate::binding::{check_journal_binding, fetch_job_binding};
+use crate::config::{parse_credits, NetworkArgs, SignerClient};
+use crate::contracts::{
+    format_credits, job_manager_contract, method_id_to_bytes_array, SetVerifierContract, JOB_STATUS_ACCEPTED,
+    JOB_STATUS_COMPLETED, JOB_STATUS_CREATED,
+};
+use crate::daemon::{self, DaemonArgs};
+use crate::job_data::JobData;
+use crate::journal::preflight_verify;
+use crate::model::import_safetensors;
+use crate::pricing::PricingArgs;
+use crate::proof_store::{ProofStore, StoredProof};
+use crate::prover::{compress_for_job, execute_job, prove_job, succinct_checkpoint};
+use crate::schedule::{check_deadline, ScheduleArgs, Scheduler};
+use crate::result::publish_result;
+use crate::seal::seal_selector;
+use crate::server::{self, ServeArgs};
+use crate::storage::{ContentStore, StorageArgs};
+use crate::tx::send_call;
+use crate::wait::poll_until;
+
+#[derive(Args, Debug)]
+pub struct ProviderArgs {
+    #[arg(long, env = "PROVIDER_PRIVATE_KEY", hide_env_values = true)]
+    private_key: String,
+
+    #[command(subcommand)]
+    command: ProviderCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ProviderCommand {
+    /// Approve and stake GPUCredit in ProviderRegistry until the desired stake is reached.
+    Stake {
+        /// Desired total stake in whole GPUCredit.
+        #[arg(long, default_value = "5")]
+        amount: String,
+    },
+    /// Accept an open job.
+    Accept {
+        #[arg(long)]
+        job_id: u64,
+    },
+    /// Run a job's guest without proving, report its cycle count and estimate what proving costs.
+    Execute {
+        #[arg(long)]
+        job_id: u64,
+        #[command(flatten)]
+        storage: StorageArgs,
+        #[command(flatten)]
+        pricing: PricingArgs,
+    },
+    /// Prove a job as a succinct receipt, wrap it in Groth16 and keep both in the proof store.
+    /// A stored succinct receipt is reused.
+    Prove {
+        #[arg(long)]
+        job_id: u64,
+        #[arg(long, env = "PROOF_DIR", default_value = "receipts")]
+        proof_dir: PathBuf,
+        /// Stop after the succinct receipt; wrap it later with `provider compress`.
+        #[arg(long)]
+        succinct_only: bool,
+        #[command(flatten)]
+        storage: StorageArgs,
+        #[command(flatten)]
+        schedule: ScheduleArgs,
+    },
+    /// Wrap a stored succinct receipt in Groth16. Needs no job data, only the job's deadline
+    /// from the network, so the proof directory can be copied to a dedicated machine.
+    Compress {
+        #[arg(long)]
+        job_id: u64,
+        #[arg(long, env = "PROOF_DIR", default_value = "receipts")]
+        proof_dir: PathBuf,
+        #[command(flatten)]
+        schedule: ScheduleArgs,
+    },
+    /// Submit a stored proof and claim the job reward.
+    Submit {
+        #[arg(long)]
+        job_id: u64,
+        #[arg(long, env = "PROOF_DIR", default_value = "receipts")]
+        proof_dir: PathBuf,
+        /// Where the result is published for `resultDataCID`.
+        #[command(flatten)]
+        storage: StorageArgs,
+    },
+    /// Prove several accepted jobs as succinct receipts, aggregate them into one Merkle root and
+    /// claim each job with its set inclusion seal.
+    Aggregate {
+        /// Accepted jobs to batch, e.g. `--job-ids 4,5,6`.
+        #[arg(long, value_delimiter = ',', required = true)]
+        job_ids: Vec<u64>,
+        #[arg(long, env = "SET_VERIFIER_ADDRESS")]
+        set_verifier_address: Address,
+        /// Set builder guest ELF whose image ID is registered in the set verifier.
+        #[arg(long, env = "SET_BUILDER_ELF")]
+        set_builder_elf: PathBuf,
+        #[arg(long, env = "PROOF_DIR", default_value = "receipts")]
+        proof_dir: PathBuf,
+        #[command(flatten)]
+        storage: StorageArgs,
+    },
+    /// Run a long-lived agent that accepts, proves and submits matching jobs as they appear.
+    Daemon(DaemonArgs),
+    /// Serve `/receive-job` for the coordinator, then prove and submit each relayed job.
+    Serve(ServeArgs),
+    /// Convert a quantized safetensors checkpoint into the model file format the guest reads.
+    ImportModel {
+        #[arg(long)]
+        safetensors: PathBuf,
+        #[arg(long)]
+        out: PathBuf,
+        /// Input image height; not recorded in the checkpoint.
+        #[arg(long, default_value_t = 32)]
+        height: usize,
+        /// Input image width; not recorded in the checkpoint.
+        #[arg(long, default_value_t = 32)]
+        width: usize,
+    },
+}
+
+pub async fn run(network: &NetworkArgs, args: ProviderArgs) -> Result<()> {
+    let provider_signer = network.signer(&args.private_key)?;
+    network.print_summary("Provider", &provider_signer);
+
+    match args.command {
+        ProviderCommand::Stake { amount } => {
+            stake(network, provider_signer, parse_credits(&amount)?).await?;
+        }
+        ProviderCommand::Accept { job_id } => {
+            accept_job(network, provider_signer.clone(), U256::from(job_id)).await?;
+        }
+        ProviderCommand::Execute { job_id, storage, pricing } => {
+            let job_id = U256::from(job_id);
+            let cost_model = pricing.cost_model()?;
+            let zk_guest_inputs = load_job_inputs(network, provider_signer.clone(), storage.open()?.as_ref(), job_id).await?;
+            let report = execute_job(job_id, &zk_guest_inputs)?;
+            let on_chain_job = network.job_manager(provider_signer.clone()).get_job(job_id).call().await?;
+            match cost_model.refuse_reason(on_chain_job.max_payment_gpu_credit, &report) {
+                Some(reason) => println!("❌ The daemon would refuse Job ID {}: {}", job_id, reason),
+                None => println!("✅ Reward {} GPUCredit covers the estimate.", format_credits(on_chain_job.max_payment_gpu_credit)),
+            }
+        }
+        ProviderCommand::Prove { job_id, proof_dir, succinct_only, storage, schedule } => {
+            let job_id = U256::from(job_id);
+            let proof_store = ProofStore::new(&proof_dir);
+            let deadline = network.job_manager(provider_signer.clone()).get_job(job_id).call().await?.deadline_timestamp;
+            let zk_guest_inputs = load_job_inputs(network, provider_signer.clone(), storage.open()?.as_ref(), job_id).await?;
+            let mut scheduler = Scheduler::open(&schedule, method_id_to_bytes_array(&RISC0_RESNET_HASHER_ID))?;
+            let estimate = scheduler.estimate(&execute_job(job_id, &zk_guest_inputs)?);
+            if succinct_only {
+                check_deadline(job_id, deadline, "the succinct proof", estimate.total_secs())?;
+                succinct_checkpoint(job_id, &zk_guest_inputs, &proof_store)?;
+            } else {
+                prove_job(job_id, &zk_guest_inputs, deadline, &estimate, &proof_store, &mut scheduler)?;
+            }
+            println!("Proof stored in {}", proof_dir.display());
+        }
+        ProviderCommand::Compress { job_id, proof_dir, schedule } => {
+            let job_id = U256::from(job_id);
+            let proof_store = ProofStore::new(&proof_dir);
+            let checkpoint = proof_store
+                .get_succinct(job_id)?
+                .ok_or_else(|| eyre::eyre!("No succinct receipt for Job ID {} in {}; run `provider prove --succinct-only` first.", job_id, proof_dir.display()))?;
+            let deadline = network.job_manager(provider_signer.clone()).get_job(job_id).call().await?.deadline_timestamp;
+            let mut scheduler = Scheduler::open(&schedule, method_id_to_bytes_array(&RISC0_RESNET_HASHER_ID))?;
+            compress_for_job(&checkpoint, deadline, &proof_store, &mut scheduler)?;
+            println!("Groth16 proof stored in {}", proof_dir.display());
+        }
+        ProviderCommand::Submit { job_id, proof_dir, storage } => {
+            let proof_store = ProofStore::new(&proof_dir);
+            let mut proof = proof_store
+                .get(U256::from(job_id))?
+                .ok_or_else(|| eyre::eyre!("No proof for Job ID {} in {}; run `provider prove` first.", job_id, proof_dir.display()))?;
+            submit_proof(network, provider_signer, &mut proof, &proof_store, storage.open()?.as_ref()).await?;
+        }
+        ProviderCommand::Aggregate { job_ids, set_verifier_address, set_builder_elf, proof_dir, storage } => {
+            let set_verifier = SetVerifierContract::new(set_verifier_address, provider_signer.clone());
+            let set_builder_elf = std::fs::read(&set_builder_elf)
+                .map_err(|e| eyre::eyre!("Cannot read set builder ELF {}: {}", set_builder_elf.display(), e))?;
+            let mut aggregator = ProofAggregator::new(set_verifier, set_builder_elf).await?;
+
+            let job_manager_provider_contract = network.job_manager(provider_signer.clone());
+            let store = storage.open()?;
+            let proof_store = ProofStore::new(&proof_dir);
+            let mut result_cids = HashMap::new();
+            for job_id in job_ids {
+                let job_id = U256::from(job_id);
+                let on_chain_job = job_manager_provider_contract.get_job(job_id).call().await?;
+                if on_chain_job.status != JOB_STATUS_ACCEPTED || on_chain_job.provider != provider_signer.address() {
+                    eyre::bail!("Job ID {} is not Accepted by this provider (status {}, provider {:?}).", job_id, on_chain_job.status, on_chain_job.provider);
+                }
+                let zk_guest_inputs = load_job_inputs(network, provider_signer.clone(), store.as_ref(), job_id).await?;
+                let zk_succinct_receipt = succinct_checkpoint(job_id, &zk_guest_inputs, &proof_store)?.receipt;
+                // A checkpoint from an earlier run may predate the current binding; JobManager
+                // would not notice, so check it here as `submit_proof` does.
+                let binding = fetch_job_binding(network, provider_signer.clone(), job_id, provider_signer.address()).await?;
+                check_journal_binding(&zk_succinct_receipt, &binding)?;
+                let logs = vec![format!("Succinct receipt, claimed through set verifier {:?}", set_verifier_address)];
+                let result_cid = publish_result(store.as_ref(), job_id, provider_signer.address(), &zk_succinct_receipt, logs).await?;
+                result_cids.insert(job_id, result_cid);
+                aggregator.push(job_id, Digest::from_bytes(on_chain_job.method_id), zk_succinct_receipt)?;
+            }
+            let mut failed = Vec::new();
+            for (job_id, aggregated_job) in aggregator.submit_batch(&network.tx).await? {
+                let submitted = match aggregated_job {
+                    Ok(aggregated_job) => submit_seal(
+                        network, provider_signer.clone(), job_id, aggregated_job.seal, aggregated_job.journal_digest, &result_cids[&job_id],
+                    )
+                    .await
+                    .map(|_| ()),
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = submitted {
+                    println!("❌ Job {} was not claimed: {:?}", job_id, e);
+                    failed.push(job_id);
+                }
+            }
+            if !failed.is_empty() {
+                eyre::bail!("{} aggregated job(s) were not claimed: {:?}", failed.len(), failed);
+            }
+        }
+        ProviderCommand::Daemon(daemon_args) => {
+            daemon::run(network, provider_signer, daemon_args).await?;
+        }
+        ProviderCommand::Serve(serve_args) => {
+            server::run(network, provider_signer, serve_args).await?;
+        }
+        ProviderCommand::ImportModel { safetensors, out, height, width } => {
+            let model_file = import_safetensors(&safetensors, height, width)?;
+            std::fs::write(&out, &model_file).map_err(|e| eyre::eyre!("Cannot write {}: {}", out.display(), e))?;
+            println!("Model file ({} bytes) written to {}", model_file.len(), out.display());
+        }
+    }
+    Ok(())
+}
+
+async fn stake(network: &NetworkArgs, provider_signer: Arc<SignerClient>, desired_stake_amount: U256) -> Result<()> {
+    if network.provider_registry_address == Address::zero() {
+        println!("\nProviderRegistry not configured. Skipping provider staking.");
+        return Ok(());
+    }
+    let gpu_credit_provider_contract = network.gpu_credit(provider_signer.clone());
+    let provider_registry_provider_contract = network.provider_registry(provider_signer.clone());
+
+    println!("\n--- Provider Staking Phase ---");
+    let provider_gcredit_balance = gpu_credit_provider_contract.balance_of(provider_signer.address()).call().await?;
+    println!("Provider current GPUCredit balance: {}", format_credits(provider_gcredit_balance));
+
+    if provider_gcredit_balance < desired_stake_amount {
+        eyre::bail!("Provider has insufficient GPUCredit ({}) to meet desired stake ({}). Please mint tokens.",
+            format_credits(provider_gcredit_balance),
+            format_credits(desired_stake_amount));
+    }
+
+    let provider_info_before_stake = provider_registry_provider_contract
+        .get_provider_info(provider_signer.address())
+        .call().await?;
+    println!("Provider current stake: {}, Exists: {}", provider_info_before_stake.stake_amount, provider_info_before_stake.exists);
+
+    if provider_info_before_stake.exists && provider_info_before_stake.stake_amount >= desired_stake_amount {
+        println!("Provider ({:?}) already has sufficient stake: {}", provider_signer.address(), provider_info_before_stake.stake_amount);
+        return Ok(());
+    }
+
+    println!("Provider needs to stake or increase stake to {}.", format_credits(desired_stake_amount));
+    let amount_to_stake_now = desired_stake_amount; // Stake the full desired amount
+
+    println!("Provider approving {} GPUCredit for ProviderRegistry...", format_credits(amount_to_stake_now));
+    let approve_stake_call = gpu_credit_provider_contract.approve(network.provider_registry_address, amount_to_stake_now);
+    let approve_stake_receipt = send_call(&network.tx, provider_signer.as_ref(), "GPUCredit approval for staking", approve_stake_call).await?;
+    println!("Stake approval successful. Tx: {:?}", approve_stake_receipt.transaction_hash);
+
+    println!("Provider calling stake() on ProviderRegistry with amount: {}", amount_to_stake_now);
+    let stake_call = provider_registry_provider_contract.stake(amount_to_stake_now);
+    let stake_receipt = send_call(&network.tx, provider_signer.as_ref(), "Provider's stake()", stake_call).await?;
+    println!("Provider stake successful. Tx: {:?}", stake_receipt.transaction_hash);
+
+    let registry = &provider_registry_provider_contract;
+    let provider_address = provider_signer.address();
+    let provider_info_after_stake = poll_until(&network.tx.wait, "the stake to show in ProviderRegistry", || async move {
+        let info = registry.get_provider_info(provider_address).call().await?;
+        Ok((info.exists && info.stake_amount >= desired_stake_amount).then_some(info))
+    })
+    .await
+    .map_err(|e| eyre::eyre!("Stake amount still insufficient after staking attempt: {}", e))?;
+    println!("Provider Info after stake: exists={}, stakeAmount={}", provider_info_after_stake.exists, provider_info_after_stake.stake_amount);
+    Ok(())
+}
+
+/// Checks that `job_id` is still open and accepts it on behalf of `provider_address`.
+pub async fn accept_job(network: &NetworkArgs, provider_signer: Arc<SignerClient>, job_id: U256) -> Result<()> {
+    let job_manager_provider_contract = network.job_manager(provider_signer.clone());
+    let provider_address = provider_signer.address();
+    println!("\nProvider ({:?}) reading on-chain job #{} details before accepting...", provider_address, job_id);
+    let job_details_before_accept: job_manager_contract::Job = job_manager_provider_contract.get_job(job_id).call().await?;
+    println!("  On-chain client:   {:?}", job_details_before_accept.client);
+    println!("  On-chain provider: {:?}", job_details_before_accept.provider);
+    println!("  On-chain status:   {:?}", job_details_before_accept.status);
+
+    if job_details_before_accept.provider != Address::zero() {
+        eyre::bail!("Job {} ALREADY HAS PROVIDER before acceptJob. State unclean.", job_id);
+    }
+    if job_details_before_accept.status != JOB_STATUS_CREATED {
+        eyre::bail!("Job {} not in 'Created' status (0). Status: {}. Aborting.", job_id, job_details_before_accept.status);
+    }
+
+    println!("Provider attempting to accept Job ID: {}...", job_id);
+    let accept_job_call = job_manager_provider_contract.accept_job(job_id);
+    let accept_job_receipt = send_call(&network.tx, provider_signer.as_ref(), &format!("acceptJob for Job ID {}", job_id), accept_job_call).await?;
+    println!("Job ID: {} accepted. Tx: {:?}", job_id, accept_job_receipt.transaction_hash);
+    let job_manager = &job_manager_provider_contract;
+    poll_until(&network.tx.wait, &format!("Job ID {} to show as accepted", job_id), || async move {
+        let job = job_manager.get_job(job_id).call().await?;
+        Ok((job.status == JOB_STATUS_ACCEPTED && job.provider == provider_address).then_some(()))
+    })
+    .await
+}
+
+/// Accepts `job_id` if it is still open, or carries on if this provider already holds it.
+/// Returns `false` when the job belongs to someone else or is no longer active.
+pub async fn accept_or_resume(network: &NetworkArgs, provider_signer: Arc<SignerClient>, job_id: U256) -> Result<bool> {
+    let provider_address = provider_signer.address();
+    let job = network.job_manager(provider_signer.clone()).get_job(job_id).call().await?;
+    if job.status == JOB_STATUS_CREATED && job.provider == Address::zero() {
+        accept_job(network, provider_signer, job_id).await?;
+        Ok(true)
+    } else if job.status == JOB_STATUS_ACCEPTED && job.provider == provider_address {
+        println!("Resuming job {} already accepted by this provider.", job_id);
+        Ok(true)
+    } else {
+        println!("Skipping job {}: status {}, provider {:?}", job_id, job.status, job.provider);
+        Ok(false)
+    }
+}
+
+/// Reads the job's `jobDataCID` from JobManager, fetches and checks the data from `store` and
+/// binds it to the job.
+pub async fn load_job_inputs(
+    network: &NetworkArgs,
+    provider_signer: Arc<SignerClient>,
+    store: &dyn ContentStore,
+    job_id: U256,
+) -> Result<JobInputs> {
+    let on_chain_job = network.job_manager(provider_signer.clone()).get_job(job_id).call().await?;
+    println!("Fetching job data for Job ID {} (CID {})...", job_id, on_chain_job.job_data_cid);
+    let job_data = JobData::from_bytes(&store.get(&on_chain_job.job_data_cid).await?)?;
+    let binding = fetch_job_binding(network, provider_signer.clone(), job_id, provider_signer.address()).await?;
+    Ok(job_data.into_inputs(binding))
+}
+
+/// Publishes the result of `proof` to `store`, then submits its seal, journal digest and result
+/// CID and checks that the reward arrived. `proof` is marked completed in `proof_store` once the
+/// job is `Completed` on-chain, whether by this call or an earlier one.
+pub async fn submit_proof(
+    network: &NetworkArgs,
+    provider_signer: Arc<SignerClient>,
+    proof: &mut StoredProof,
+    proof_store: &ProofStore,
+    store: &dyn ContentStore,
+) -> Result<()> {
+    let job_id = proof.job_id;
+    let job_manager_provider_contract = network.job_manager(provider_signer.clone());
+    let on_chain_job = job_manager_provider_contract.get_job(job_id).call().await?;
+    if on_chain_job.status == JOB_STATUS_COMPLETED {
+        println!("Job ID {} is already Completed on-chain; nothing to submit.", job_id);
+        proof.completed = true;
+        return proof_store.put(proof);
+    }
+
+    let selector = seal_selector(&proof.seal)?;
+    println!("Stored seal selector: {:?} ({}), Size: {} bytes", selector, selector, proof.seal.len());
+
+    // Verify against the methodId stored in the job, exactly as JobManager will.
+    println!("Pre-flight: verifying receipt locally against job methodId 0x{}...", hex::encode(on_chain_job.method_id));
+    let journal_hash_for_contract_array: [u8; 32] = preflight_verify(&proof.receipt, Digest::from_bytes(on_chain_job.method_id))?;
+    if journal_hash_for_contract_array != proof.journal_hash {
+        eyre::bail!("Stored journal hash for Job ID {} does not match its receipt.", job_id);
+    }
+    let binding = fetch_job_binding(network, provider_signer.clone(), job_id, provider_signer.address()).await?;
+    check_journal_binding(&proof.receipt, &binding)?;
+    println!("Pre-flight verification passed.");
+
+    let logs = vec![
+        format!("Groth16 seal, selector {} ({} bytes)", selector, proof.seal.len()),
+        format!("Verified locally against methodId 0x{}", hex::encode(on_chain_job.method_id)),
+        format!(
+            "Proved in {:.1}s: {} segments, {} total cycles ({} user)",
+            proof.stats.proving_secs, proof.stats.segments, proof.stats.total_cycles, proof.stats.user_cycles
+        ),
+    ];
+    let result_cid = publish_result(store, job_id, provider_signer.address(), &proof.receipt, logs).await?;
+    let submit_tx = submit_seal(network, provider_signer, job_id, proof.seal.clone(), journal_hash_for_contract_array, &result_cid).await?;
+    proof.submit_tx = Some(submit_tx);
+    proof.completed = true;
+    proof_store.put(proof)
+}
+
+/// Retries submission of every stored proof that is not known to be completed, e.g. after a
+/// crash between proving and claiming. Jobs no longer ours are left alone.
+pub async fn resume_pending_proofs(
+    network: &NetworkArgs,
+    provider_signer: Arc<SignerClient>,
+    proof_store: &ProofStore,
+    store: &dyn ContentStore,
+) -> Result<()> {
+    let job_manager_provider_contract = network.job_manager(provider_signer.clone());
+    for mut proof in proof_store.pending()? {
+        let job = job_manager_provider_contract.get_job(proof.job_id).call().await?;
+        let ours = job.provider == provider_signer.address();
+        if (job.status == JOB_STATUS_ACCEPTED && ours) || job.status == JOB_STATUS_COMPLETED {
+            println!("Resuming submission of stored proof for Job ID {}...", proof.job_id);
+            if let Err(e) = submit_proof(network, provider_signer.clone(), &mut proof, proof_store, store).await {
+                println!("❌ Stored proof for Job ID {} could not be submitted: {:?}", proof.job_id, e);
+            }
+        } else {
+            println!("Stored proof for Job ID {} is no longer claimable: status {}, provider {:?}", proof.job_id, job.status, job.provider);
+        }
+    }
+    Ok(())
+}
+
+/// Sends `submitProofAndClaim` with an already encoded seal, checks that the reward arrived and
+/// returns the transaction hash.
+pub async fn submit_seal(
+    network: &NetworkArgs,
+    provider_signer: Arc<SignerClient>,
+    job_id: U256,
+    seal: Vec<u8>,
+    journal_hash_for_contract_array: [u8; 32],
+    result_cid_for_contract: &str,
+) -> Result<H256> {
+    let gpu_credit_provider_contract = network.gpu_credit(provider_signer.clone());
+    let job_manager_provider_contract = network.job_manager(provider_signer.clone());
+    let seal_for_contract = Bytes::from(seal);
+    println!("JournalHash (sha256) for contract: 0x{}", hex::encode(journal_hash_for_contract_array));
+
+    let provider_balance_before_submit = gpu_credit_provider_contract.balance_of(provider_signer.address()).call().await?;
+    println!("\nProvider GPUCredit balance before submit: {}", format_credits(provider_balance_before_submit));
+    println!("Provider submitting proof data to JobManager (Job ID: {})...", job_id);
+    let submit_proof_call = job_manager_provider_contract.submit_proof_and_claim(
+        job_id, seal_for_contract, journal_hash_for_contract_array, result_cid_for_contract.to_string());
+    let submit_proof_receipt_mined =
+        send_call(&network.tx, provider_signer.as_ref(), &format!("submitProofAndClaim for Job ID {}", job_id), submit_proof_call).await?;
+    println!("Proof data submitted and transaction SUCCEEDED! Tx: {:?}", submit_proof_receipt_mined.transaction_hash);
+
+    // --- Check Provider's GPUCredit Balance After Reward ---
+    let gpu_credit = &gpu_credit_provider_contract;
+    let provider_address = provider_signer.address();
+    let rewarded = poll_until(&network.tx.wait, "the reward to arrive", || async move {
+        let balance = gpu_credit.balance_of(provider_address).call().await?;
+        Ok((balance > provider_balance_before_submit).then_some(balance))
+    })
+    .await;
+    match rewarded {
+        Ok(_) => println!("✅ Success! Provider received GPUCredit. On-chain ZK verification passed!"),
+        Err(e) => println!("❌ Error: Provider balance did not increase AFTER successful submitProofAndClaim tx ({}). Check payment logic in contract or events.", e),
+    }
+    Ok(submit_proof_receipt_mined.transaction_hash)
+}
@@ -0,0 +1,135 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ethers::prelude::*;
+use eyre::Result;
+use methods::JobOutputs;
+use risc0_zkvm::Receipt;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{NetworkArgs, SignerClient};
+use crate::contracts::JobProofVerifiedFilter;
+use crate::journal::journal_digest;
+use crate::storage::ContentStore;
+
+/// What a provider publishes under `resultDataCID`: the journal decoded, the receipt that proves
+/// it and how it was produced.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobResult {
+    pub job_id: U256,
+    pub provider: Address,
+    pub outputs: JobOutputs,
+    /// Argmax of each sampled image's logits, in `outputs.sampled_indices` order.
+    pub predicted_classes: Vec<usize>,
+    /// The receipt whose journal hash is claimed on-chain.
+    pub receipt: Receipt,
+    pub logs: Vec<String>,
+}
+
+impl JobResult {
+    pub fn new(job_id: U256, provider: Address, receipt: &Receipt, mut logs: Vec<String>) -> Result<Self> {
+        let outputs: JobOutputs = receipt.journal.decode().map_err(|e| eyre::eyre!("Journal decode failed: {:?}", e))?;
+        let published_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        logs.push(format!("Journal sha256: 0x{}", hex::encode(journal_digest(&receipt.journal.bytes))));
+        logs.push(format!("Published at unix time {} by {:?}", published_at, provider));
+        Ok(Self { job_id, provider, predicted_classes: outputs.predicted_classes(), outputs, receipt: receipt.clone(), logs })
+    }
+
+    /// bincode, the same encoding as receipts on disk.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        Ok(bincode::serialize(self)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        bincode::deserialize(bytes).map_err(|e| eyre::eyre!("Result data is not a bincode JobResult: {}", e))
+    }
+
+    /// `sha256(journal)` of the published receipt, as `submitProofAndClaim` receives it.
+    pub fn journal_hash(&self) -> [u8; 32] {
+        journal_digest(&self.receipt.journal.bytes)
+    }
+
+    /// Fails unless the receipt's journal hashes to `journal_hash` and decodes to `outputs`.
+    pub fn check_journal(&self, journal_hash: [u8; 32]) -> Result<()> {
+        if self.journal_hash() != journal_hash {
+            eyre::bail!(
+                "Published receipt journal hashes to 0x{}, but 0x{} was verified on-chain.",
+                hex::encode(self.journal_hash()),
+                hex::encode(journal_hash)
+            );
+        }
+        let outputs: JobOutputs = self.receipt.journal.decode().map_err(|e| eyre::eyre!("Journal decode failed: {:?}", e))?;
+        if outputs != self.outputs || outputs.predicted_classes() != self.predicted_classes {
+            eyre::bail!("Published outputs differ from the receipt journal.");
+        }
+        Ok(())
+    }
+}
+
+/// Serializes the result for `receipt`, stores it and returns its CID for `submitProofAndClaim`.
+pub async fn publish_result(store: &dyn ContentStore, job_id: U256, provider: Address, receipt: &Receipt, logs: Vec<String>) -> Result<String> {
+    let result = JobResult::new(job_id, provider, receipt, logs)?.to_bytes()?;
+    println!("Publishing {} bytes of result data for Job ID {}...", result.len(), job_id);
+    let cid = store.put(&result).await?;
+    println!("Result data CID: {}", cid);
+    Ok(cid)
+}
+
+/// The journal hash JobManager accepted for `job_id`, from its `JobProofVerified` event.
+pub async fn verified_journal_hash(network: &NetworkArgs, signer: Arc<SignerClient>, job_id: U256) -> Result<[u8; 32]> {
+    let (verified, _) = network.find_job_event::<JobProofVerifiedFilter>(signer, job_id, |event| event.job_id == job_id).await?;
+    Ok(verified.journal_hash)
+}
+
+/// Downloads the result of `job_id` by its `resultDataCID` and checks it against the journal
+/// hash JobManager verified.
+pub async fn fetch_result(network: &NetworkArgs, signer: Arc<SignerClient>, store: &dyn ContentStore, job_id: U256) -> Result<JobResult> {
+    let on_chain_job = network.job_manager(signer.clone()).get_job(job_id).call().await?;
+    if on_chain_job.result_data_cid.is_empty() {
+        eyre::bail!("Job ID {} has no resultDataCID yet (status {}).", job_id, on_chain_job.status);
+    }
+    println!("Fetching result data for Job ID {} (CID {})...", job_id, on_chain_job.result_data_cid);
+    let result = JobResult::from_bytes(&store.get(&on_chain_job.result_data_cid).await?)?;
+    if result.job_id != job_id {
+        eyre::bail!("Result data is for Job ID {}, not {}.", result.job_id, job_id);
+    }
+
+    let journal_hash = verified_journal_hash(network, signer, job_id).await?;
+    result.check_journal(journal_hash)?;
+    println!("Result matches the on-chain journal hash 0x{}.", hex::encode(journal_hash));
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use methods::{JobBinding, Preprocessing};
+    use risc0_zkvm::{FakeReceipt, InnerReceipt, ReceiptClaim};
+
+    fn fake_receipt(outputs: &JobOutputs) -> Receipt {
+        let journal: Vec<u8> = risc0_zkvm::serde::to_vec(outputs).unwrap().iter().flat_map(|word| word.to_le_bytes()).collect();
+        let claim = ReceiptClaim::ok(risc0_zkvm::sha::Digest::ZERO, journal.clone());
+        Receipt::new(InnerReceipt::Fake(FakeReceipt::new(claim)), journal)
+    }
+
+    #[test]
+    fn result_round_trips_and_checks_journal_hash() {
+        let outputs = JobOutputs {
+            binding: JobBinding { job_id: [0; 32], chain_id: 421614, job_manager: [1; 20], client: [2; 20], seed: [3; 32] },
+            image_batch_hash: [4; 32],
+            model_weights_hash: [5; 32],
+            preprocessing: Preprocessing { resize: 32, crop_height: 32, crop_width: 32, mean: vec![0.5; 3], std: vec![0.25; 3], input_scale: 1.0 / 32.0 },
+            batch_size: 2,
+            sampled_indices: vec![0, 1],
+            num_classes: 2,
+            logits: vec![1, 9, 7, 3],
+        };
+        let receipt = fake_receipt(&outputs);
+        let result = JobResult::new(U256::from(7u64), Address::repeat_byte(9), &receipt, vec![]).unwrap();
+        assert_eq!(result.predicted_classes, [1, 0]);
+
+        let decoded = JobResult::from_bytes(&result.to_bytes().unwrap()).unwrap();
+        decoded.check_journal(journal_digest(&receipt.journal.bytes)).unwrap();
+        assert!(decoded.check_journal([0; 32]).is_err());
+    }
+}
@@ -0,0 +1,187 @@
+use std::fmt;
+
+use ethers::contract::{ContractError, ContractRevert};
+use ethers::prelude::*;
+use ethers::providers::MiddlewareError;
+
+use crate::contracts::{
+    gpu_credit_contract::GPUCreditContractErrors, job_manager_contract::JobManagerContractErrors,
+    provider_registry_contract::ProviderRegistryContractErrors, set_verifier_contract::SetVerifierContractErrors, JOB_STATUS_ACCEPTED, JOB_STATUS_COMPLETED, JOB_STATUS_CREATED,
+};
+
+/// A revert from one of the marketplace contracts, decoded from its custom error.
+///
+/// JobManager bubbles up reverts from ProviderRegistry and GPUCredit, so revert data is tried
+/// against all three, and then against the set verifier that aggregated batches go through.
+#[derive(Debug, Clone)]
+pub enum RevertReason {
+    JobManager(JobManagerContractErrors),
+    ProviderRegistry(ProviderRegistryContractErrors),
+    GPUCredit(GPUCreditContractErrors),
+    SetVerifier(SetVerifierContractErrors),
+    /// Revert data none of the marketplace contracts declare.
+    Unknown(Bytes),
+}
+
+impl RevertReason {
+    pub fn decode(data: &[u8]) -> Self {
+        if let Some(err) = JobManagerContractErrors::decode_with_selector(data) {
+            Self::JobManager(err)
+        } else if let Some(err) = ProviderRegistryContractErrors::decode_with_selector(data) {
+            Self::ProviderRegistry(err)
+        } else if let Some(err) = GPUCreditContractErrors::decode_with_selector(data) {
+            Self::GPUCredit(err)
+        } else if let Some(err) = SetVerifierContractErrors::decode_with_selector(data) {
+            Self::SetVerifier(err)
+        } else {
+            Self::Unknown(Bytes::from(data.to_vec()))
+        }
+    }
+}
+
+impl fmt::Display for RevertReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::JobManager(JobManagerContractErrors::InvalidJobStatus(e)) => {
+                write!(f, "InvalidJobStatus: job is {} but must be {}", status_name(e.current), status_name(e.required))
+            }
+            Self::JobManager(JobManagerContractErrors::ProviderNotRegisteredOrInsufficientStake(_)) => {
+                write!(f, "ProviderNotRegisteredOrInsufficientStake: stake at least minProviderStakeRequired with `provider stake`")
+            }
+            Self::JobManager(JobManagerContractErrors::ZKProofVerificationFailed(_)) => {
+                write!(f, "ZKProofVerificationFailed: the verifier router rejected the seal for this job's methodId and journal hash")
+            }
+            Self::JobManager(JobManagerContractErrors::DeadlinePassed(_)) => {
+                write!(f, "DeadlinePassed: the job's deadlineTimestamp has passed")
+            }
+            Self::JobManager(JobManagerContractErrors::JobAlreadyHasProvider(_)) => {
+                write!(f, "JobAlreadyHasProvider: another provider accepted the job first")
+            }
+            Self::JobManager(JobManagerContractErrors::NotJobProvider(_)) => write!(f, "NotJobProvider: the job is assigned to another provider"),
+            Self::JobManager(JobManagerContractErrors::RevertString(reason)) => write!(f, "{}", reason),
+            Self::JobManager(err) => write!(f, "JobManager {:?}", err),
+            // The contract reverts with `(stakeAmount, _amount)`, so `required` holds the stake.
+            Self::ProviderRegistry(ProviderRegistryContractErrors::InsufficientStake(e)) => {
+                write!(f, "InsufficientStake: cannot unstake {} with only {} staked", e.actual, e.required)
+            }
+            Self::ProviderRegistry(ProviderRegistryContractErrors::RevertString(reason)) => write!(f, "{}", reason),
+            Self::ProviderRegistry(err) => write!(f, "ProviderRegistry {:?}", err),
+            Self::GPUCredit(GPUCreditContractErrors::ERC20InsufficientBalance(e)) => {
+                write!(f, "ERC20InsufficientBalance: {:?} holds {} but needs {}", e.sender, e.balance, e.needed)
+            }
+            Self::GPUCredit(GPUCreditContractErrors::ERC20InsufficientAllowance(e)) => {
+                write!(f, "ERC20InsufficientAllowance: {:?} may spend {} but needs {}", e.spender, e.allowance, e.needed)
+            }
+            Self::GPUCredit(GPUCreditContractErrors::RevertString(reason)) => write!(f, "{}", reason),
+            Self::GPUCredit(err) => write!(f, "GPUCredit {:?}", err),
+            Self::SetVerifier(SetVerifierContractErrors::VerificationFailed(_)) => {
+                write!(f, "VerificationFailed: the set verifier rejected the root seal or Merkle path")
+            }
+            Self::SetVerifier(SetVerifierContractErrors::SelectorMismatch(e)) => write!(
+                f,
+                "SelectorMismatch: seal has selector 0x{} but the set verifier expects 0x{}",
+                hex::encode(e.received),
+                hex::encode(e.expected)
+            ),
+            Self::SetVerifier(err) => write!(f, "SetVerifier {:?}", err),
+            Self::Unknown(data) if data.is_empty() => write!(f, "reverted without data"),
+            Self::Unknown(data) => write!(f, "unknown revert data {}", data),
+        }
+    }
+}
+
+fn status_name(status: u8) -> String {
+    match status {
+        JOB_STATUS_CREATED => "Created".to_string(),
+        JOB_STATUS_ACCEPTED => "Accepted".to_string(),
+        JOB_STATUS_COMPLETED => "Completed".to_string(),
+        3 => "Cancelled".to_string(),
+        4 => "Disputed".to_string(),
+        other => format!("status {}", other),
+    }
+}
+
+/// Decodes the revert behind a failed `eth_call` or `eth_estimateGas`, if it was one.
+pub fn decode_contract_err<M: Middleware>(err: &ContractError<M>) -> Option<RevertReason> {
+    err.as_revert().map(|data| RevertReason::decode(data))
+}
+
+/// The decoded revert reason of `err`, or `err` itself if it is not a revert.
+pub fn describe_contract_err<M: Middleware>(err: &ContractError<M>) -> String {
+    match decode_contract_err(err) {
+        Some(reason) => reason.to_string(),
+        None => err.to_string(),
+    }
+}
+
+/// Replays a mined transaction that failed as an `eth_call` against the state before its block,
+/// which reproduces the revert data receipts do not carry. The call runs at the parent block's
+/// state, so earlier transactions in the same block are not applied and a revert that depends
+/// on one of them may not be reproduced.
+pub async fn replay_failed_tx<M: Middleware>(client: &M, receipt: &TransactionReceipt) -> Result<RevertReason, String> {
+    let tx = client
+        .get_transaction(receipt.transaction_hash)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("transaction {:?} not found", receipt.transaction_hash))?;
+    let block = receipt.block_number.ok_or("receipt has no block number")?;
+    let mut call = TransactionRequest::new().from(tx.from).data(tx.input).value(tx.value).gas(tx.gas);
+    if let Some(to) = tx.to {
+        call = call.to(to);
+    }
+    match client.call(&call.into(), Some(BlockId::Number((block.as_u64().saturating_sub(1)).into()))).await {
+        Ok(_) => Err("the replay succeeded, so the revert depended on an earlier transaction in its block".to_string()),
+        Err(e) => match e.as_error_response().and_then(|response| response.as_revert_data()) {
+            Some(data) => Ok(RevertReason::decode(&data)),
+            None => Err(e.to_string()),
+        },
+    }
+}
+
+/// The error for a transaction that was mined but reverted, with its replayed reason.
+pub async fn reverted<M: Middleware>(client: &M, what: &str, receipt: &TransactionReceipt) -> eyre::Report {
+    let reason = match replay_failed_tx(client, receipt).await {
+        Ok(reason) => reason.to_string(),
+        Err(e) => format!("reason unavailable: {}", e),
+    };
+    eyre::eyre!("{} REVERTED: {}. Tx: {:?}", what, reason, receipt.transaction_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::{encode, Token};
+    use ethers::utils::id;
+
+    fn revert_data(signature: &str, args: &[Token]) -> Vec<u8> {
+        [id(signature).to_vec(), encode(args)].concat()
+    }
+
+    #[test]
+    fn decodes_custom_errors_of_each_contract() {
+        let status = RevertReason::decode(&revert_data("InvalidJobStatus(uint8,uint8)", &[Token::Uint(1.into()), Token::Uint(0.into())]));
+        assert_eq!(status.to_string(), "InvalidJobStatus: job is Accepted but must be Created");
+        assert!(matches!(
+            RevertReason::decode(&revert_data("ZKProofVerificationFailed()", &[])),
+            RevertReason::JobManager(JobManagerContractErrors::ZKProofVerificationFailed(_))
+        ));
+
+        let stake = RevertReason::decode(&revert_data("InsufficientStake(uint256,uint256)", &[Token::Uint(5.into()), Token::Uint(7.into())]));
+        assert_eq!(stake.to_string(), "InsufficientStake: cannot unstake 7 with only 5 staked");
+        let balance = RevertReason::decode(&revert_data(
+            "ERC20InsufficientBalance(address,uint256,uint256)",
+            &[Token::Address(Address::zero()), Token::Uint(1.into()), Token::Uint(2.into())],
+        ));
+        assert!(matches!(balance, RevertReason::GPUCredit(GPUCreditContractErrors::ERC20InsufficientBalance(_))));
+
+        let mismatch = RevertReason::decode(&revert_data(
+            "SelectorMismatch(bytes4,bytes4)",
+            &[Token::FixedBytes(vec![1, 2, 3, 4]), Token::FixedBytes(vec![0x80, 0x47, 0x9d, 0x24])],
+        ));
+        assert_eq!(mismatch.to_string(), "SelectorMismatch: seal has selector 0x01020304 but the set verifier expects 0x80479d24");
+
+        let message = RevertReason::decode(&revert_data("Error(string)", &[Token::String("paused".into())]));
+        assert_eq!(message.to_string(), "paused");
+        assert!(matches!(RevertReason::decode(&[0xde, 0xad]), RevertReason::Unknown(_)));
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::Args;
+use ethers::types::U256;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::fs::write_atomic;
+use crate::proof_store::ProvingStats;
+use crate::prover::ExecutionReport;
+
+/// Past proving runs kept per guest.
+const MAX_RECORDS_PER_GUEST: usize = 100;
+/// Records closest in size that an estimate is based on.
+const NEAREST_RECORDS: usize = 5;
+
+/// Options for deciding whether a job can be proven before its deadline.
+#[derive(Args, Debug, Clone)]
+pub struct ScheduleArgs {
+    /// Proving times of past jobs, per guest, used to predict new ones.
+    #[arg(long, env = "PROVING_HISTORY", default_value = "proving-history.json")]
+    history_file: PathBuf,
+    /// Refuse jobs not expected to be submitted at least this many seconds before their deadline.
+    #[arg(long, default_value_t = 600)]
+    deadline_margin_secs: u64,
+    /// Succinct proving speed assumed while a guest has no history, in seconds per million padded cycles.
+    #[arg(long, default_value_t = 60.0)]
+    default_secs_per_mcycle: f64,
+    /// Groth16 wrapping time assumed while a guest has no history.
+    #[arg(long, default_value_t = 120.0)]
+    default_groth16_secs: f64,
+}
+
+/// One finished proof. Input size is measured in padded cycles, which the executor reports
+/// before anything is proven.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProvingRecord {
+    pub padded_cycles: u64,
+    pub segments: usize,
+    pub proving_secs: f64,
+    pub groth16_secs: f64,
+}
+
+/// Proving records by guest method ID (hex), oldest first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProvingHistory {
+    guests: BTreeMap<String, Vec<ProvingRecord>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProvingEstimate {
+    pub succinct_secs: f64,
+    pub groth16_secs: f64,
+    /// Past runs the estimate is based on; zero means the configured defaults were used.
+    pub samples: usize,
+}
+
+impl ProvingEstimate {
+    pub fn total_secs(&self) -> f64 {
+        self.succinct_secs + self.groth16_secs
+    }
+}
+
+impl ProvingHistory {
+    pub fn record(&mut self, method_id: [u8; 32], stats: &ProvingStats) {
+        let records = self.guests.entry(hex::encode(method_id)).or_default();
+        records.push(ProvingRecord {
+            padded_cycles: stats.total_cycles,
+            segments: stats.segments,
+            proving_secs: stats.proving_secs,
+            groth16_secs: stats.groth16_secs,
+        });
+        if records.len() > MAX_RECORDS_PER_GUEST {
+            records.drain(..records.len() - MAX_RECORDS_PER_GUEST);
+        }
+    }
+
+    /// Scales the proving speed of the past runs closest in size to `padded_cycles`, falling back
+    /// to the defaults while this guest has none.
+    pub fn estimate(&self, method_id: [u8; 32], padded_cycles: u64, default_secs_per_mcycle: f64, default_groth16_secs: f64) -> ProvingEstimate {
+        let mut nearest: Vec<&ProvingRecord> =
+            self.guests.get(&hex::encode(method_id)).into_iter().flatten().filter(|record| record.padded_cycles > 0).collect();
+        nearest.sort_by_key(|record| record.padded_cycles.abs_diff(padded_cycles));
+        nearest.truncate(NEAREST_RECORDS);
+        if nearest.is_empty() {
+            return ProvingEstimate {
+                succinct_secs: default_secs_per_mcycle * padded_cycles as f64 / 1e6,
+                groth16_secs: default_groth16_secs,
+                samples: 0,
+            };
+        }
+
+        let secs_per_cycle = nearest.iter().map(|record| record.proving_secs / record.padded_cycles as f64).sum::<f64>() / nearest.len() as f64;
+        let wrapped: Vec<f64> = nearest.iter().map(|record| record.groth16_secs).filter(|secs| *secs > 0.0).collect();
+        let groth16_secs = if wrapped.is_empty() { default_groth16_secs } else { wrapped.iter().sum::<f64>() / wrapped.len() as f64 };
+        ProvingEstimate { succinct_secs: secs_per_cycle * padded_cycles as f64, groth16_secs, samples: nearest.len() }
+    }
+}
+
+/// Predicts proving times from [ProvingHistory] and holds jobs to their deadlines.
+pub struct Scheduler {
+    history_file: PathBuf,
+    history: ProvingHistory,
+    method_id: [u8; 32],
+    deadline_margin_secs: u64,
+    default_secs_per_mcycle: f64,
+    default_groth16_secs: f64,
+}
+
+impl Scheduler {
+    pub fn open(args: &ScheduleArgs, method_id: [u8; 32]) -> Result<Self> {
+        let history = match std::fs::read_to_string(&args.history_file) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| eyre::eyre!("Corrupt proving history {}: {}", args.history_file.display(), e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => ProvingHistory::default(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            history_file: args.history_file.clone(),
+            history,
+            method_id,
+            deadline_margin_secs: args.deadline_margin_secs,
+            default_secs_per_mcycle: args.default_secs_per_mcycle,
+            default_groth16_secs: args.default_groth16_secs,
+        })
+    }
+
+    pub fn deadline_margin_secs(&self) -> u64 {
+        self.deadline_margin_secs
+    }
+
+    pub fn estimate(&self, report: &ExecutionReport) -> ProvingEstimate {
+        self.estimate_cycles(report.padded_cycles)
+    }
+
+    /// Same as [Scheduler::estimate], for a job whose padded cycle count is already known, e.g.
+    /// from its succinct proof.
+    pub fn estimate_cycles(&self, padded_cycles: u64) -> ProvingEstimate {
+        self.history.estimate(self.method_id, padded_cycles, self.default_secs_per_mcycle, self.default_groth16_secs)
+    }
+
+    /// Explains why a job executed as `report` will not be proven by `deadline` with the margin
+    /// to spare, if it will not.
+    pub fn refuse_reason(&self, deadline: U256, report: &ExecutionReport) -> Result<Option<String>> {
+        let estimate = self.estimate(report);
+        let time_left = deadline_secs(deadline) as f64 - now_secs()?;
+        println!(
+            "Expected proving time: {:.0}s succinct + {:.0}s Groth16 ({}), {:.0}s left before the deadline",
+            estimate.succinct_secs,
+            estimate.groth16_secs,
+            if estimate.samples == 0 { "defaults, no history yet".to_string() } else { format!("from {} past jobs", estimate.samples) },
+            time_left
+        );
+        let needed = estimate.total_secs() + self.deadline_margin_secs as f64;
+        Ok((time_left < needed).then(|| {
+            format!("expected to need {:.0}s including {}s margin, but only {:.0}s are left before the deadline", needed, self.deadline_margin_secs, time_left)
+        }))
+    }
+
+    /// Adds a finished proof to the history and saves it.
+    pub fn record(&mut self, stats: &ProvingStats) -> Result<()> {
+        self.history.record(self.method_id, stats);
+        write_atomic(&self.history_file, serde_json::to_string_pretty(&self.history)?.as_bytes())
+    }
+}
+
+/// Checks an accepted job against its deadline before a stage expected to take `remaining_secs`
+/// up to submission. Warns if the job is likely to be late and fails if it will obviously be
+/// late, since `submitProofAndClaim` then reverts and proving further only wastes the prover.
+pub fn check_deadline(job_id: U256, deadline: U256, stage: &str, remaining_secs: f64) -> Result<()> {
+    let time_left = deadline_secs(deadline) as f64 - now_secs()?;
+    if time_left <= 0.0 || time_left < remaining_secs / 2.0 {
+        eyre::bail!(
+            "Abandoning Job ID {} before {}: {:.0}s left before the deadline, but about {:.0}s of work remain.",
+            job_id,
+            stage,
+            time_left.max(0.0),
+            remaining_secs
+        );
+    }
+    if time_left < remaining_secs {
+        println!(
+            "⚠️ Job ID {} may miss its deadline: {:.0}s left before {}, about {:.0}s of work remain.",
+            job_id, time_left, stage, remaining_secs
+        );
+    }
+    Ok(())
+}
+
+/// A `deadlineTimestamp` in seconds, saturating for deadlines too far out to matter.
+fn deadline_secs(deadline: U256) -> u64 {
+    if deadline > U256::from(u64::MAX) {
+        u64::MAX
+    } else {
+        deadline.as_u64()
+    }
+}
+
+fn now_secs() -> Result<f64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs_f64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(total_cycles: u64, proving_secs: f64, groth16_secs: f64) -> ProvingStats {
+        ProvingStats { total_cycles, proving_secs, groth16_secs, ..ProvingStats::default() }
+    }
+
+    #[test]
+    fn estimate_scales_nearest_history_and_falls_back_to_defaults() {
+        let guest = [1; 32];
+        let mut history = ProvingHistory::default();
+        assert_eq!(history.estimate(guest, 2_000_000, 60.0, 120.0), ProvingEstimate { succinct_secs: 120.0, groth16_secs: 120.0, samples: 0 });
+
+        // 10s per million cycles near 1M, far slower on much larger jobs.
+        for _ in 0..NEAREST_RECORDS {
+            history.record(guest, &stats(1 << 20, 10.48576, 40.0));
+        }
+        history.record(guest, &stats(64 << 20, 6710.8864, 50.0));
+        let estimate = history.estimate(guest, 2 << 20, 60.0, 120.0);
+        assert!((estimate.succinct_secs - 20.97152).abs() < 1e-9);
+        assert_eq!((estimate.groth16_secs, estimate.samples), (40.0, NEAREST_RECORDS));
+        assert_eq!(history.estimate([2; 32], 1_000_000, 60.0, 120.0).samples, 0);
+
+        check_deadline(U256::from(u64::MAX), U256::MAX, "Groth16", 100.0).unwrap();
+        assert!(check_deadline(U256::zero(), U256::from(1u64), "Groth16", 0.0).is_err());
+    }
+}
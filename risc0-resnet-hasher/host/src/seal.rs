@@ -0,0 +1,57 @@
+use eyre::Result;
+use risc0_ethereum_contracts::encode_seal;
+use risc0_ethereum_contracts::selector::{Selector, SelectorType};
+use risc0_zkvm::Receipt;
+
+/// Encodes `receipt` as a selector-prefixed seal for `RiscZeroVerifierRouter`.
+///
+/// The router picks a verifier from the first 4 bytes of the seal, so a raw Groth16 seal is never
+/// routable. Returns the seal together with the [Selector] it will be routed on.
+pub fn encode_router_seal(receipt: &Receipt) -> Result<(Vec<u8>, Selector)> {
+    let seal = encode_seal(receipt).map_err(|e| eyre::eyre!("Seal encoding failed: {}", e))?;
+    let selector = router_seal_selector(&seal)?;
+    Ok((seal, selector))
+}
+
+/// Reads the selector of a seal sent straight to the router, which must be Groth16 or fake.
+fn router_seal_selector(seal: &[u8]) -> Result<Selector> {
+    let selector = seal_selector(seal)?;
+    match selector.get_type() {
+        SelectorType::Groth16 | SelectorType::FakeReceipt => Ok(selector),
+        other => eyre::bail!("Receipt encoded with {:?} selector {}; expected a Groth16 seal.", other, selector),
+    }
+}
+
+/// Reads the verifier selector of a seal and checks that it is one the router can know about.
+pub fn seal_selector(seal: &[u8]) -> Result<Selector> {
+    if seal.len() < 4 {
+        eyre::bail!("Seal too short ({} bytes) to carry a selector.", seal.len());
+    }
+    let selector_bytes = [seal[0], seal[1], seal[2], seal[3]];
+    Selector::from_bytes(selector_bytes)
+        .ok_or_else(|| eyre::eyre!("Unknown verifier selector 0x{}; the router will reject this seal.", hex::encode(selector_bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use risc0_zkvm::sha::Digestible;
+    use risc0_zkvm::{FakeReceipt, InnerReceipt, ReceiptClaim};
+
+    #[test]
+    fn fake_receipt_seal_is_prefixed_and_other_selectors_are_rejected() {
+        let claim = ReceiptClaim::ok([7u32; 8], vec![1, 2, 3]);
+        let receipt = Receipt::new(InnerReceipt::Fake(FakeReceipt::new(claim.clone())), vec![1, 2, 3]);
+        let (seal, selector) = encode_router_seal(&receipt).unwrap();
+        assert_eq!(selector, Selector::FakeReceipt);
+        assert_eq!(seal[..4], [0xFF; 4]);
+        assert_eq!(seal[4..], *claim.digest().as_bytes());
+
+        let groth16 = (Selector::Groth16V2_0 as u32).to_be_bytes();
+        assert_eq!(router_seal_selector(&groth16).unwrap(), Selector::Groth16V2_0);
+        let set_verifier = (Selector::SetVerifierV0_6 as u32).to_be_bytes();
+        assert!(router_seal_selector(&set_verifier).unwrap_err().to_string().contains("expected a Groth16 seal"));
+        assert!(seal_selector(&[0x12, 0x34, 0x56, 0x78]).unwrap_err().to_string().contains("Unknown verifier selector 0x12345678"));
+        assert!(seal_selector(&[0xFF; 3]).is_err());
+    }
+}
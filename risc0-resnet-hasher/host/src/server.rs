@@ -0,0 +1,382 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use clap::Args;
+use ethers::prelude::*;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use methods::RISC0_RESNET_HASHER_ID;
+
+use crate::binding::fetch_job_binding;
+use crate::config::{NetworkArgs, SignerClient};
+use crate::contracts::{method_id_to_bytes_array, JOB_STATUS_CREATED};
+use crate::coordinator::ReceiveJobRequest;
+use crate::fs::write_atomic;
+use crate::pricing::{CostModel, PricingArgs};
+use crate::proof_store::ProofStore;
+use crate::prover::{execute_job, prove_job};
+use crate::provider::{accept_or_resume, load_job_inputs, resume_pending_proofs, submit_proof};
+use crate::schedule::{check_deadline, ScheduleArgs, Scheduler};
+use crate::storage::{ContentStore, StorageArgs};
+
+/// Options for the provider HTTP server.
+#[derive(Args, Debug, Clone)]
+pub struct ServeArgs {
+    /// Address the `/receive-job` and `/jobs/{id}` endpoints listen on.
+    #[arg(long, env = "PROVIDER_LISTEN", default_value = "0.0.0.0:3001")]
+    listen: SocketAddr,
+    /// Proofs are kept here until their job is completed, and resubmitted on restart.
+    #[arg(long, env = "PROOF_DIR", default_value = "receipts")]
+    proof_dir: PathBuf,
+    /// Received jobs and their states, so a restart resumes unfinished jobs and `/jobs/{id}`
+    /// keeps answering for them.
+    #[arg(long, env = "PROVIDER_JOBS_FILE", default_value = "provider-jobs.json")]
+    jobs_file: PathBuf,
+    #[command(flatten)]
+    storage: StorageArgs,
+    #[command(flatten)]
+    pricing: PricingArgs,
+    #[command(flatten)]
+    schedule: ScheduleArgs,
+}
+
+/// Where a received job is in the pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Queued,
+    FetchingInputs,
+    Executing,
+    Accepting,
+    Proving,
+    Submitting,
+    Completed,
+    Skipped,
+    Failed,
+}
+
+impl JobState {
+    pub fn is_final(self) -> bool {
+        matches!(self, JobState::Completed | JobState::Skipped | JobState::Failed)
+    }
+}
+
+/// Body of `GET /jobs/{id}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobStatusReport {
+    pub job_id: String,
+    pub cid: String,
+    pub seed: String,
+    pub state: JobState,
+    pub error: Option<String>,
+    /// Unix seconds of the last state change.
+    pub updated_at: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct ReceiveJobResponse {
+    message: String,
+    #[serde(rename = "jobId")]
+    job_id: String,
+}
+
+/// Received jobs by ID, written to `--jobs-file` on every change.
+pub struct JobBook {
+    path: PathBuf,
+    reports: Mutex<HashMap<U256, JobStatusReport>>,
+}
+
+impl JobBook {
+    pub fn open(path: &std::path::Path) -> Result<Self> {
+        let reports: Vec<JobStatusReport> = match std::fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| eyre::eyre!("Corrupt jobs file {}: {}", path.display(), e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let reports = reports
+            .into_iter()
+            .map(|report| Ok((U256::from_dec_str(&report.job_id)?, report)))
+            .collect::<Result<HashMap<_, _>>>()?;
+        Ok(Self { path: path.to_path_buf(), reports: Mutex::new(reports) })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<U256, JobStatusReport>> {
+        self.reports.lock().expect("job book poisoned")
+    }
+
+    fn get(&self, job_id: U256) -> Option<JobStatusReport> {
+        self.lock().get(&job_id).cloned()
+    }
+
+    /// Records `request` as queued. Returns false, changing nothing, if the job was already
+    /// received and has not failed.
+    fn receive(&self, job_id: U256, request: &ReceiveJobRequest) -> Result<bool> {
+        let mut reports = self.lock();
+        if reports.get(&job_id).is_some_and(|report| report.state != JobState::Failed) {
+            return Ok(false);
+        }
+        reports.insert(
+            job_id,
+            JobStatusReport {
+                job_id: request.job_id.clone(),
+                cid: request.cid.clone(),
+                seed: request.seed.clone(),
+                state: JobState::Queued,
+                error: None,
+                updated_at: unix_now(),
+            },
+        );
+        self.persist(&reports)?;
+        Ok(true)
+    }
+
+    fn set_state(&self, job_id: U256, state: JobState, error: Option<String>) {
+        let mut reports = self.lock();
+        if let Some(report) = reports.get_mut(&job_id) {
+            report.state = state;
+            report.error = error;
+            report.updated_at = unix_now();
+        }
+        if let Err(e) = self.persist(&reports) {
+            println!("⚠️ Could not save job states to {}: {}", self.path.display(), e);
+        }
+    }
+
+    /// Jobs a previous run received but did not finish, oldest update first.
+    fn unfinished(&self) -> Vec<ReceiveJobRequest> {
+        let mut reports: Vec<JobStatusReport> = self.lock().values().filter(|report| !report.state.is_final()).cloned().collect();
+        reports.sort_by_key(|report| report.updated_at);
+        reports
+            .into_iter()
+            .map(|report| ReceiveJobRequest { job_id: report.job_id, cid: report.cid, seed: report.seed })
+            .collect()
+    }
+
+    fn persist(&self, reports: &HashMap<U256, JobStatusReport>) -> Result<()> {
+        let mut sorted: Vec<&JobStatusReport> = reports.values().collect();
+        sorted.sort_by_key(|report| U256::from_dec_str(&report.job_id).unwrap_or_default());
+        write_atomic(&self.path, serde_json::to_string_pretty(&sorted)?.as_bytes())
+    }
+}
+
+#[derive(Clone)]
+struct AppState {
+    jobs: Arc<JobBook>,
+    queue: mpsc::UnboundedSender<ReceiveJobRequest>,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+/// Serves `/receive-job` and `/jobs/{id}` and proves received jobs one at a time. Jobs left
+/// unfinished by a previous run are queued again first.
+pub async fn run(network: &NetworkArgs, provider_signer: Arc<SignerClient>, args: ServeArgs) -> Result<()> {
+    let jobs = Arc::new(JobBook::open(&args.jobs_file)?);
+    let (queue, mut received) = mpsc::unbounded_channel::<ReceiveJobRequest>();
+
+    let worker = Worker {
+        network: network.clone(),
+        provider_signer,
+        proof_store: ProofStore::new(&args.proof_dir),
+        store: args.storage.open()?,
+        cost_model: args.pricing.cost_model()?,
+        scheduler: Arc::new(Mutex::new(Scheduler::open(&args.schedule, method_id_to_bytes_array(&RISC0_RESNET_HASHER_ID))?)),
+        jobs: jobs.clone(),
+    };
+    resume_pending_proofs(network, worker.provider_signer.clone(), &worker.proof_store, worker.store.as_ref()).await?;
+    for request in jobs.unfinished() {
+        println!("Resuming job {} received before the restart.", request.job_id);
+        jobs.set_state(U256::from_dec_str(&request.job_id)?, JobState::Queued, None);
+        queue.send(request)?;
+    }
+    tokio::spawn(async move {
+        while let Some(request) = received.recv().await {
+            let job_id = U256::from_dec_str(&request.job_id).unwrap_or_default();
+            match worker.process_job(&request).await {
+                Ok((state, reason)) => worker.jobs.set_state(job_id, state, reason),
+                Err(e) => {
+                    println!("❌ Job {} failed: {:?}", request.job_id, e);
+                    worker.jobs.set_state(job_id, JobState::Failed, Some(format!("{:#}", e)));
+                }
+            }
+        }
+    });
+
+    let app = Router::new()
+        .route("/receive-job", post(receive_job))
+        .route("/jobs/{id}", get(job_status))
+        .with_state(AppState { jobs, queue });
+    let listener = tokio::net::TcpListener::bind(args.listen).await?;
+    println!("\n--- Provider Server ---");
+    println!("Listening on {} (POST /receive-job, GET /jobs/{{id}})", args.listen);
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+async fn receive_job(
+    State(state): State<AppState>,
+    Json(request): Json<ReceiveJobRequest>,
+) -> (StatusCode, Json<ReceiveJobResponse>) {
+    let reject = |message: &str| {
+        (StatusCode::BAD_REQUEST, Json(ReceiveJobResponse { message: message.to_string(), job_id: String::new() }))
+    };
+    if request.cid.is_empty() || request.seed.is_empty() {
+        return reject("Missing job data (jobId, cid, or seed)");
+    }
+    let Ok(job_id) = U256::from_dec_str(&request.job_id) else {
+        return reject("jobId must be a decimal uint256");
+    };
+    if request.seed.parse::<H256>().is_err() {
+        return reject("seed must be a 0x-prefixed bytes32");
+    }
+
+    match state.jobs.receive(job_id, &request) {
+        Ok(true) => {}
+        Ok(false) => return (StatusCode::OK, Json(ReceiveJobResponse { message: "Job already received".into(), job_id: request.job_id })),
+        Err(e) => {
+            let message = format!("Could not record job: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(ReceiveJobResponse { message, job_id: request.job_id }));
+        }
+    }
+    println!("Queued job {}. CID: {}, Seed: {}", request.job_id, request.cid, request.seed);
+    let job_id_text = request.job_id.clone();
+    if state.queue.send(request).is_err() {
+        return (StatusCode::SERVICE_UNAVAILABLE, Json(ReceiveJobResponse { message: "Prover worker stopped".into(), job_id: job_id_text }));
+    }
+    (StatusCode::OK, Json(ReceiveJobResponse { message: "Job queued by provider".into(), job_id: job_id_text }))
+}
+
+async fn job_status(State(state): State<AppState>, Path(id): Path<String>) -> Result<Json<JobStatusReport>, StatusCode> {
+    let job_id = U256::from_dec_str(&id).map_err(|_| StatusCode::BAD_REQUEST)?;
+    state.jobs.get(job_id).map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Proves received jobs one at a time.
+struct Worker {
+    network: NetworkArgs,
+    provider_signer: Arc<SignerClient>,
+    proof_store: ProofStore,
+    store: Box<dyn ContentStore>,
+    cost_model: CostModel,
+    scheduler: Arc<Mutex<Scheduler>>,
+    jobs: Arc<JobBook>,
+}
+
+impl Worker {
+    /// Checks, accepts, proves and submits one job. Returns the final state and, for a skipped job,
+    /// why.
+    ///
+    /// The request, price and deadline are all checked before `acceptJob`: acceptance commits the
+    /// provider, and a job it then abandons counts against it.
+    async fn process_job(&self, request: &ReceiveJobRequest) -> Result<(JobState, Option<String>)> {
+        let Worker { network, provider_signer, proof_store, store, cost_model, scheduler, jobs } = self;
+        let store = store.as_ref();
+        let job_id = U256::from_dec_str(&request.job_id)?;
+        let on_chain_job = network.job_manager(provider_signer.clone()).get_job(job_id).call().await?;
+        if on_chain_job.job_data_cid != request.cid {
+            eyre::bail!("CID {} from the coordinator does not match jobDataCID {} of Job ID {}.", request.cid, on_chain_job.job_data_cid, job_id);
+        }
+        // The coordinator derived the seed for the provider it picked; it must be us.
+        let binding = fetch_job_binding(network, provider_signer.clone(), job_id, provider_signer.address()).await?;
+        let expected_seed: H256 = request.seed.parse()?;
+        if binding.seed != expected_seed.to_fixed_bytes() {
+            eyre::bail!("Seed {:?} from the coordinator does not match 0x{} derived for this provider.", expected_seed, hex::encode(binding.seed));
+        }
+
+        let stored_proof = proof_store.get(job_id)?;
+        let mut to_prove = None;
+        if stored_proof.is_none() {
+            jobs.set_state(job_id, JobState::FetchingInputs, None);
+            let zk_guest_inputs = load_job_inputs(network, provider_signer.clone(), store, job_id).await?;
+            jobs.set_state(job_id, JobState::Executing, None);
+            let execute_inputs = zk_guest_inputs.clone();
+            let report = tokio::task::spawn_blocking(move || execute_job(job_id, &execute_inputs)).await??;
+            // A job already ours is finished whatever it costs.
+            if on_chain_job.status == JOB_STATUS_CREATED {
+                let refused = match cost_model.refuse_reason(on_chain_job.max_payment_gpu_credit, &report) {
+                    Some(reason) => Some(reason),
+                    None => scheduler.lock().expect("scheduler poisoned").refuse_reason(on_chain_job.deadline_timestamp, &report)?,
+                };
+                if let Some(reason) = refused {
+                    println!("Skipping job {}: {}", job_id, reason);
+                    return Ok((JobState::Skipped, Some(reason)));
+                }
+            }
+            let estimate = scheduler.lock().expect("scheduler poisoned").estimate(&report);
+            to_prove = Some((zk_guest_inputs, estimate));
+        }
+
+        jobs.set_state(job_id, JobState::Accepting, None);
+        if !accept_or_resume(network, provider_signer.clone(), job_id).await? {
+            return Ok((JobState::Skipped, Some("Job is no longer open to this provider.".into())));
+        }
+
+        let deadline = on_chain_job.deadline_timestamp;
+        let mut proof = match (stored_proof, to_prove) {
+            (Some(proof), _) => proof,
+            (None, Some((zk_guest_inputs, estimate))) => {
+                jobs.set_state(job_id, JobState::Proving, None);
+                let checkpoint_store = proof_store.clone();
+                let scheduler = scheduler.clone();
+                tokio::task::spawn_blocking(move || {
+                    let mut scheduler = scheduler.lock().expect("scheduler poisoned");
+                    prove_job(job_id, &zk_guest_inputs, deadline, &estimate, &checkpoint_store, &mut scheduler)
+                })
+                .await??
+            }
+            (None, None) => eyre::bail!("Job ID {} changed state while it was being checked; retry it.", job_id),
+        };
+
+        check_deadline(job_id, deadline, "submission", 0.0)?;
+        jobs.set_state(job_id, JobState::Submitting, None);
+        submit_proof(network, provider_signer.clone(), &mut proof, proof_store, store).await?;
+        Ok((JobState::Completed, None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn receive_job_queues_once_and_reports_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("provider-jobs.json");
+        let (queue, mut received) = mpsc::unbounded_channel();
+        let state = AppState { jobs: Arc::new(JobBook::open(&path).unwrap()), queue };
+        let request = || ReceiveJobRequest { job_id: "7".into(), cid: "QmTest".into(), seed: format!("{:?}", H256::repeat_byte(1)) };
+
+        let (status, _) = receive_job(State(state.clone()), Json(request())).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = receive_job(State(state.clone()), Json(request())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(received.recv().await.unwrap().job_id, "7");
+        assert!(received.try_recv().is_err());
+
+        let Json(report) = job_status(State(state.clone()), Path("7".into())).await.unwrap();
+        assert_eq!(report.state, JobState::Queued);
+        assert_eq!(serde_json::to_value(&report).unwrap()["state"], "queued");
+        assert_eq!(job_status(State(state.clone()), Path("8".into())).await.unwrap_err(), StatusCode::NOT_FOUND);
+
+        let (status, _) = receive_job(State(state), Json(ReceiveJobRequest { seed: "0x12".into(), ..request() })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // After a restart the queued job is still reported and comes back for proving.
+        let reopened = JobBook::open(&path).unwrap();
+        assert_eq!(reopened.get(U256::from(7)).unwrap().state, JobState::Queued);
+        assert_eq!(reopened.unfinished().iter().map(|request| request.job_id.as_str()).collect::<Vec<_>>(), vec!["7"]);
+        reopened.set_state(U256::from(7), JobState::Completed, None);
+        assert!(JobBook::open(&path).unwrap().unfinished().is_empty());
+    }
+}
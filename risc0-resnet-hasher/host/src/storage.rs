@@ -0,0 +1,210 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use clap::{Args, ValueEnum};
+use eyre::Result;
+use serde::Deserialize;
+use sha2::{Digest as _, Sha256};
+
+/// CIDv1 prefix for a single raw block with a sha2-256 multihash: version 1, codec `raw`
+/// (0x55), hash `sha2-256` (0x12), digest length 32.
+const RAW_SHA256_CID_PREFIX: [u8; 4] = [0x01, 0x55, 0x12, 0x20];
+const BASE32_ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+/// The CID IPFS gives `bytes` when stored as one raw block (`ipfs block put --cid-codec raw`).
+pub fn raw_cid(bytes: &[u8]) -> String {
+    let mut cid = RAW_SHA256_CID_PREFIX.to_vec();
+    cid.extend_from_slice(&Sha256::digest(bytes));
+    format!("b{}", base32_encode(&cid))
+}
+
+/// Fails unless `bytes` hash to `cid`. Only CIDv1 raw sha2-256 CIDs, as made by [raw_cid],
+/// can be checked against plain bytes; anything else is refused.
+pub fn verify_cid(cid: &str, bytes: &[u8]) -> Result<()> {
+    let decoded = cid
+        .strip_prefix('b')
+        .and_then(base32_decode)
+        .filter(|decoded| decoded.len() == 36 && decoded[..4] == RAW_SHA256_CID_PREFIX)
+        .ok_or_else(|| eyre::eyre!("Unsupported CID {}: expected a CIDv1 raw sha2-256 CID (bafkrei...).", cid))?;
+    if decoded[4..] != Sha256::digest(bytes)[..] {
+        eyre::bail!("Content does not match CID {} (got {}).", cid, raw_cid(bytes));
+    }
+    Ok(())
+}
+
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 31) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+    }
+    out
+}
+
+fn base32_decode(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() * 5 / 8);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for c in text.bytes() {
+        let value = BASE32_ALPHABET.iter().position(|&a| a == c.to_ascii_lowercase())? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+/// Somewhere job data and results can be stored and fetched by CID.
+#[async_trait]
+pub trait ContentStore: Send + Sync {
+    /// Stores `bytes` and returns their CID.
+    async fn put(&self, bytes: &[u8]) -> Result<String>;
+
+    /// Fetches the bytes stored under `cid` without checking them.
+    async fn fetch(&self, cid: &str) -> Result<Vec<u8>>;
+
+    /// Fetches the bytes stored under `cid` and checks they hash to it.
+    async fn get(&self, cid: &str) -> Result<Vec<u8>> {
+        let bytes = self.fetch(cid).await?;
+        verify_cid(cid, &bytes)?;
+        Ok(bytes)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StorageBackend {
+    /// Files named by CID in `--storage-dir`; for local testing or a shared mount.
+    Local,
+    /// Read-only HTTP gateway serving `<gateway>/ipfs/<cid>`.
+    Gateway,
+    /// Kubo RPC API; uploads are pinned.
+    Ipfs,
+}
+
+/// Where job data and results are stored.
+#[derive(Args, Debug, Clone)]
+pub struct StorageArgs {
+    #[arg(long, env = "STORAGE_BACKEND", value_enum, default_value_t = StorageBackend::Local)]
+    storage: StorageBackend,
+    #[arg(long, env = "STORAGE_DIR", default_value = "job-data")]
+    storage_dir: PathBuf,
+    #[arg(long, env = "IPFS_GATEWAY_URL", default_value = "http://127.0.0.1:8080")]
+    gateway_url: String,
+    #[arg(long, env = "IPFS_API_URL", default_value = "http://127.0.0.1:5001")]
+    ipfs_api_url: String,
+}
+
+impl StorageArgs {
+    pub fn open(&self) -> Result<Box<dyn ContentStore>> {
+        let http = reqwest::Client::builder().timeout(Duration::from_secs(300)).build()?;
+        Ok(match self.storage {
+            StorageBackend::Local => Box::new(LocalDirStore { dir: self.storage_dir.clone() }),
+            StorageBackend::Gateway => Box::new(GatewayStore { url: self.gateway_url.trim_end_matches('/').to_string(), http }),
+            StorageBackend::Ipfs => Box::new(IpfsStore { api_url: self.ipfs_api_url.trim_end_matches('/').to_string(), http }),
+        })
+    }
+}
+
+pub struct LocalDirStore {
+    dir: PathBuf,
+}
+
+#[async_trait]
+impl ContentStore for LocalDirStore {
+    async fn put(&self, bytes: &[u8]) -> Result<String> {
+        let cid = raw_cid(bytes);
+        tokio::fs::create_dir_all(&self.dir).await?;
+        tokio::fs::write(self.dir.join(&cid), bytes).await?;
+        Ok(cid)
+    }
+
+    async fn fetch(&self, cid: &str) -> Result<Vec<u8>> {
+        let path = self.dir.join(cid);
+        tokio::fs::read(&path).await.map_err(|e| eyre::eyre!("Cannot read {}: {}", path.display(), e))
+    }
+}
+
+pub struct GatewayStore {
+    url: String,
+    http: reqwest::Client,
+}
+
+#[async_trait]
+impl ContentStore for GatewayStore {
+    async fn put(&self, _bytes: &[u8]) -> Result<String> {
+        eyre::bail!("The gateway backend is read-only; upload with --storage ipfs or --storage local.")
+    }
+
+    async fn fetch(&self, cid: &str) -> Result<Vec<u8>> {
+        // `format=raw` asks for the block itself rather than a rendered UnixFS file.
+        let url = format!("{}/ipfs/{}?format=raw", self.url, cid);
+        let response = self.http.get(&url).header("Accept", "application/vnd.ipld.raw").send().await?.error_for_status()?;
+        Ok(response.bytes().await?.to_vec())
+    }
+}
+
+pub struct IpfsStore {
+    api_url: String,
+    http: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+struct BlockPutResponse {
+    #[serde(rename = "Key")]
+    key: String,
+}
+
+#[async_trait]
+impl ContentStore for IpfsStore {
+    async fn put(&self, bytes: &[u8]) -> Result<String> {
+        let url = format!("{}/api/v0/block/put?cid-codec=raw&mhtype=sha2-256&pin=true&allow-big-block=true", self.api_url);
+        let form = reqwest::multipart::Form::new().part("file", reqwest::multipart::Part::bytes(bytes.to_vec()));
+        let response: BlockPutResponse = self.http.post(&url).multipart(form).send().await?.error_for_status()?.json().await?;
+        let expected = raw_cid(bytes);
+        if response.key != expected {
+            eyre::bail!("IPFS stored the data as {}, expected {}.", response.key, expected);
+        }
+        Ok(response.key)
+    }
+
+    async fn fetch(&self, cid: &str) -> Result<Vec<u8>> {
+        let url = format!("{}/api/v0/block/get?arg={}", self.api_url, cid);
+        let response = self.http.post(&url).send().await?.error_for_status()?;
+        Ok(response.bytes().await?.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_cid_matches_ipfs() {
+        // `ipfs block put --cid-codec raw` of an empty file.
+        assert_eq!(raw_cid(b""), "bafkreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku");
+        verify_cid(&raw_cid(b"job data"), b"job data").unwrap();
+        assert!(verify_cid(&raw_cid(b"job data"), b"job dato").is_err());
+        assert!(verify_cid("QmRisc0StakingAndProofJob", b"job data").is_err());
+    }
+
+    #[tokio::test]
+    async fn local_store_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = LocalDirStore { dir: dir.path().to_path_buf() };
+        let cid = store.put(b"job data").await.unwrap();
+        assert_eq!(store.get(&cid).await.unwrap(), b"job data");
+        std::fs::write(dir.path().join(&cid), b"tampered").unwrap();
+        assert!(store.get(&cid).await.is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use ethers::abi::Detokenize;
+use ethers::prelude::*;
+use eyre::Result;
+
+use crate::config::SignerClient;
+use crate::fees::{estimate_gas_components, latest_base_fee, FeeArgs, FeeEstimate, GasComponents};
+use crate::nonce::NonceManagers;
+use crate::revert::{describe_contract_err, reverted};
+use crate::wait::{wait_for_confirmations, WaitArgs};
+
+/// How state-changing transactions are sent.
+#[derive(Args, Debug, Clone)]
+pub struct TxArgs {
+    /// Send transactions even when their `eth_call` simulation reverts.
+    #[arg(long, global = true)]
+    pub force: bool,
+    /// Gas limit for a forced transaction whose gas cannot be estimated.
+    #[arg(long, global = true, default_value_t = 3_000_000)]
+    pub force_gas_limit: u64,
+    /// Transactions sent but not yet mined, per signer, so a restart resumes them instead of
+    /// leaving nonce gaps.
+    #[arg(long, global = true, env = "PENDING_TX_DIR", default_value = "pending-txs")]
+    pub pending_tx_dir: PathBuf,
+    /// Replace a transaction with higher fees once it has been pending this long.
+    #[arg(long, global = true, default_value_t = 90)]
+    pub replace_after_secs: u64,
+    /// Fee increase per replacement, in percent; nodes require at least 10.
+    #[arg(long, global = true, default_value_t = 20, value_parser = clap::value_parser!(u64).range(10..))]
+    pub gas_bump_pct: u64,
+    #[command(flatten)]
+    pub fees: FeeArgs,
+    #[command(flatten)]
+    pub wait: WaitArgs,
+    /// Nonces handed out by this process.
+    #[arg(skip)]
+    pub nonces: NonceManagers,
+}
+
+/// Simulates `call` with `eth_call`, estimates its L2 and L1 gas and reports what it should cost,
+/// then sends it through the signer's nonce manager and waits for the receipt and
+/// `--confirmations`. A call that would revert is not sent unless `--force` is given; either way
+/// the revert is decoded. One that breaks the fee caps is never sent.
+pub async fn send_call<D: Detokenize>(tx_args: &TxArgs, client: &SignerClient, what: &str, call: ContractCall<SignerClient, D>) -> Result<TransactionReceipt> {
+    check_simulation(tx_args, what, call.call().await.map(|_| ()).map_err(|e| describe_contract_err(&e)))?;
+
+    let mut request = call.tx;
+    let estimate = match estimate_gas_components(client, &request).await {
+        Ok(components) => FeeEstimate::new(components, &tx_args.fees)?,
+        Err(e) => {
+            if !tx_args.force {
+                eyre::bail!("Gas estimation for {} failed: {}", what, e);
+            }
+            println!("Gas estimation for {} FAILED: {}. Using --force-gas-limit {}.", what, e, tx_args.force_gas_limit);
+            forced_estimate(tx_args, latest_base_fee(client).await?)?
+        }
+    };
+    estimate.print_report(what);
+    estimate.apply(&mut request);
+    let receipt = tx_args.nonces.get(client, tx_args).await?.send(client, tx_args, what, request).await?;
+    if receipt.status != Some(1.into()) {
+        return Err(reverted(client, what, &receipt).await);
+    }
+    wait_for_confirmations(client, &tx_args.wait, what, receipt).await
+}
+
+/// Decides whether a call whose `eth_call` simulation ended as `simulated` may be sent: a
+/// decoded revert stops it unless `--force` is given.
+fn check_simulation(tx_args: &TxArgs, what: &str, simulated: std::result::Result<(), String>) -> Result<()> {
+    if let Err(reason) = simulated {
+        if !tx_args.force {
+            eyre::bail!("{} would revert: {}. Not sent; pass --force to send it anyway.", what, reason);
+        }
+        println!("⚠️ {} would revert ({}); sending anyway because of --force.", what, reason);
+    }
+    Ok(())
+}
+
+/// Fees for a forced transaction whose gas could not be estimated: `--force-gas-limit` as the
+/// limit, still within the fee caps.
+fn forced_estimate(tx_args: &TxArgs, base_fee: U256) -> Result<FeeEstimate> {
+    let gas_limit = U256::from(tx_args.force_gas_limit);
+    let components = GasComponents { l2_gas: gas_limit, l1_gas: U256::zero(), base_fee, l1_base_fee: None };
+    Ok(FeeEstimate { gas_limit, ..FeeEstimate::new(components, &tx_args.fees)? })
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        tx: TxArgs,
+    }
+
+    fn tx_args(args: &[&str]) -> TxArgs {
+        Cli::parse_from(std::iter::once("gpu-market").chain(args.iter().copied())).tx
+    }
+
+    #[test]
+    fn reverting_calls_are_sent_only_with_force() {
+        let reverted = || Err("JobManager: JobNotOpen()".to_string());
+        let refused = check_simulation(&tx_args(&[]), "acceptJob", reverted()).unwrap_err().to_string();
+        assert!(refused.contains("JobNotOpen") && refused.contains("--force"), "{}", refused);
+        check_simulation(&tx_args(&["--force"]), "acceptJob", reverted()).unwrap();
+        check_simulation(&tx_args(&[]), "acceptJob", Ok(())).unwrap();
+
+        let forced = forced_estimate(&tx_args(&["--force", "--force-gas-limit", "500000"]), 100_000_000.into()).unwrap();
+        assert_eq!(forced.gas_limit, U256::from(500_000));
+        assert_eq!(forced.max_fee_per_gas, U256::from(200_000_000u64));
+        assert!(forced_estimate(&tx_args(&["--force", "--max-fee-gwei", "0.05"]), 100_000_000.into()).is_err());
+    }
+}
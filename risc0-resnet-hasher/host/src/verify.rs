@@ -0,0 +1,198 @@
+use std::sync::Arc;
+
+use ethers::abi::AbiDecode;
+use ethers::prelude::*;
+use eyre::Result;
+use methods::sampling::sample_indices;
+use methods::weights::ModelFile;
+use methods::JobOutputs;
+use risc0_ethereum_contracts::receipt::{decode_seal, Receipt as DecodedReceipt};
+use risc0_zkvm::sha::{Digest, Impl as ShaImpl, Sha256};
+
+use crate::binding::fetch_job_binding;
+use crate::config::{NetworkArgs, SignerClient};
+use crate::contracts::{JobProofVerifiedFilter, SubmitProofAndClaimCall, JOB_STATUS_COMPLETED};
+use crate::job_data::JobData;
+use crate::result::JobResult;
+use crate::storage::ContentStore;
+
+/// Outcome of one independent check.
+struct Check {
+    name: &'static str,
+    outcome: Result<(), String>,
+}
+
+impl Check {
+    fn new(name: &'static str, outcome: Result<(), String>) -> Self {
+        Self { name, outcome }
+    }
+
+    fn expect<T: PartialEq + std::fmt::Debug>(name: &'static str, actual: T, expected: T) -> Self {
+        let outcome = if actual == expected { Ok(()) } else { Err(format!("got {:?}, expected {:?}", actual, expected)) };
+        Self { name, outcome }
+    }
+}
+
+/// Re-checks a completed job without trusting the provider: the seal it submitted, the result it
+/// published and the job data the client uploaded must all agree.
+pub async fn verify_job(network: &NetworkArgs, signer: Arc<SignerClient>, store: &dyn ContentStore, job_id: U256) -> Result<()> {
+    let job_manager = network.job_manager(signer.clone());
+    let on_chain_job = job_manager.get_job(job_id).call().await?;
+    println!("\n--- Verifying Job ID {} ---", job_id);
+    println!("Status: {}, provider {:?}, methodId 0x{}", on_chain_job.status, on_chain_job.provider, hex::encode(on_chain_job.method_id));
+    if on_chain_job.status != JOB_STATUS_COMPLETED {
+        eyre::bail!("Job ID {} is not completed (status {}).", job_id, on_chain_job.status);
+    }
+    let method_id = Digest::from_bytes(on_chain_job.method_id);
+
+    // The seal only appears in the calldata of the transaction that emitted JobProofVerified.
+    let (verified, meta) = network.find_job_event::<JobProofVerifiedFilter>(signer.clone(), job_id, |event| event.job_id == job_id).await?;
+    println!("JobProofVerified in tx {:?}: journalHash 0x{}", meta.transaction_hash, hex::encode(verified.journal_hash));
+    let submit_tx = signer
+        .get_transaction(meta.transaction_hash)
+        .await?
+        .ok_or_else(|| eyre::eyre!("Transaction {:?} not found.", meta.transaction_hash))?;
+
+    let result = JobResult::from_bytes(&store.get(&on_chain_job.result_data_cid).await?)?;
+    let job_data = JobData::from_bytes(&store.get(&on_chain_job.job_data_cid).await?)?;
+    let outputs: JobOutputs = result.receipt.journal.decode().map_err(|e| eyre::eyre!("Journal decode failed: {:?}", e))?;
+
+    let mut checks = submission_checks(&submit_tx.input, job_id, verified.journal_hash, &on_chain_job.result_data_cid, method_id, &result)?;
+    let binding = fetch_job_binding(network, signer, job_id, on_chain_job.provider).await?;
+    checks.push(Check::expect("journal is bound to this job and provider seed", outputs.binding.clone(), binding));
+    checks.push(Check::expect("image batch hash matches uploaded job data", outputs.image_batch_hash, sha256(&job_data.image_batch_data)));
+    checks.push(Check::expect("model weights hash matches uploaded job data", outputs.model_weights_hash, sha256(&job_data.model_weights_data)));
+    checks.push(Check::expect("preprocessing matches uploaded job data", outputs.preprocessing.clone(), job_data.preprocessing.clone()));
+    let model = ModelFile::parse(&job_data.model_weights_data).map_err(|e| eyre::eyre!("Uploaded model file: {:?}", e))?;
+    let batch_size = (job_data.image_batch_data.len() / model.config.image_len()) as u32;
+    checks.push(Check::expect("batch size matches uploaded images", outputs.batch_size, batch_size));
+    checks.push(Check::expect(
+        "sampled indices follow the job seed",
+        outputs.sampled_indices.clone(),
+        sample_indices(&outputs.binding.seed, batch_size, job_data.sample_size),
+    ));
+
+    let mut failed = 0;
+    for check in &checks {
+        match &check.outcome {
+            Ok(()) => println!("✅ {}", check.name),
+            Err(e) => {
+                failed += 1;
+                println!("❌ {}: {}", check.name, e);
+            }
+        }
+    }
+    if failed > 0 {
+        eyre::bail!("{} of {} checks failed for Job ID {}.", failed, checks.len(), job_id);
+    }
+    println!("Predicted Classes: {:?}", outputs.predicted_classes());
+    println!("All {} checks passed for Job ID {}.", checks.len(), job_id);
+    Ok(())
+}
+
+/// Decodes the `submitProofAndClaim` calldata `input` and checks it and the published `result`
+/// against the `JobProofVerified` journal hash and the job's `resultDataCID`.
+fn submission_checks(
+    input: &[u8],
+    job_id: U256,
+    verified_journal_hash: [u8; 32],
+    result_data_cid: &str,
+    method_id: Digest,
+    result: &JobResult,
+) -> Result<Vec<Check>> {
+    let submit_call = SubmitProofAndClaimCall::decode(input).map_err(|e| eyre::eyre!("Tx is not a direct submitProofAndClaim call: {}", e))?;
+    let journal = result.receipt.journal.bytes.clone();
+    Ok(vec![
+        Check::expect("result is for this job", result.job_id, job_id),
+        Check::expect("journal hash matches JobProofVerified", result.journal_hash(), verified_journal_hash),
+        Check::expect("submitted journal hash matches JobProofVerified", submit_call.journal_hash, verified_journal_hash),
+        Check::expect("submitted resultDataCID matches getJob", submit_call.result_data_cid.as_str(), result_data_cid),
+        Check::new("published receipt verifies against methodId", result.receipt.verify(method_id).map_err(|e| e.to_string())),
+        Check::new("on-chain seal verifies against methodId and journal", verify_seal(submit_call.seal.to_vec(), method_id, journal)),
+    ])
+}
+
+/// Rebuilds the receipt the verifier router checked from the raw seal and verifies it.
+fn verify_seal(seal: Vec<u8>, method_id: Digest, journal: Vec<u8>) -> Result<(), String> {
+    match decode_seal(seal.into(), method_id, journal).map_err(|e| e.to_string())? {
+        DecodedReceipt::Base(receipt) => receipt.verify(method_id).map_err(|e| e.to_string()),
+        // Only the Merkle path is in the seal; the root was proven to the set verifier on-chain.
+        DecodedReceipt::SetInclusion(_) => {
+            println!("Seal is a set inclusion seal; its root is only checked by the on-chain set verifier.");
+            Ok(())
+        }
+    }
+}
+
+fn sha256(bytes: &[u8]) -> [u8; 32] {
+    (*ShaImpl::hash_bytes(bytes)).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::AbiEncode;
+    use methods::{JobBinding, Preprocessing};
+    use risc0_aggregation::SetInclusionReceipt;
+    use risc0_ethereum_contracts::encode_seal;
+    use risc0_ethereum_contracts::selector::Selector;
+    use risc0_zkvm::{FakeReceipt, InnerReceipt, Receipt, ReceiptClaim};
+
+    fn outcome(checks: &[Check], name: &str) -> Result<(), String> {
+        checks.iter().find(|check| check.name == name).unwrap().outcome.clone()
+    }
+
+    #[test]
+    fn decodes_submission_and_compares_journal_and_cid() {
+        let job_id = U256::from(7u64);
+        let method_id = Digest::from([7u32; 8]);
+        let outputs = JobOutputs {
+            binding: JobBinding { job_id: [0; 32], chain_id: 421614, job_manager: [1; 20], client: [2; 20], seed: [3; 32] },
+            image_batch_hash: [4; 32],
+            model_weights_hash: [5; 32],
+            preprocessing: Preprocessing { resize: 32, crop_height: 32, crop_width: 32, mean: vec![0.5; 3], std: vec![0.25; 3], input_scale: 1.0 / 32.0 },
+            batch_size: 2,
+            sampled_indices: vec![0, 1],
+            num_classes: 2,
+            logits: vec![1, 9, 7, 3],
+        };
+        let journal: Vec<u8> = risc0_zkvm::serde::to_vec(&outputs).unwrap().iter().flat_map(|word| word.to_le_bytes()).collect();
+        let claim = ReceiptClaim::ok(method_id, journal.clone());
+        let receipt = Receipt::new(InnerReceipt::Fake(FakeReceipt::new(claim.clone())), journal);
+        let result = JobResult::new(job_id, Address::repeat_byte(9), &receipt, vec![]).unwrap();
+        let journal_hash = result.journal_hash();
+
+        // An aggregated job's seal holds only its Merkle path; the root was checked on-chain.
+        let verifier_parameters = Selector::SetVerifierV0_6.verifier_parameters_digest().unwrap();
+        let inclusion = SetInclusionReceipt::from_path_with_verifier_params(claim, vec![Digest::from([1u32; 8])], verifier_parameters);
+        let seal = encode_seal(&inclusion).unwrap();
+        let calldata = |seal: &[u8], journal_hash: [u8; 32], result_data_cid: &str| {
+            SubmitProofAndClaimCall { job_id, seal: seal.to_vec().into(), journal_hash, result_data_cid: result_data_cid.into() }.encode()
+        };
+
+        let checks = submission_checks(&calldata(&seal, journal_hash, "bafyresult"), job_id, journal_hash, "bafyresult", method_id, &result).unwrap();
+        for name in [
+            "result is for this job",
+            "journal hash matches JobProofVerified",
+            "submitted journal hash matches JobProofVerified",
+            "submitted resultDataCID matches getJob",
+            "on-chain seal verifies against methodId and journal",
+        ] {
+            assert_eq!(outcome(&checks, name), Ok(()), "{}", name);
+        }
+
+        let checks = submission_checks(&calldata(&seal, [0; 32], "bafyother"), job_id, journal_hash, "bafyresult", method_id, &result).unwrap();
+        assert!(outcome(&checks, "submitted journal hash matches JobProofVerified").is_err());
+        assert!(outcome(&checks, "submitted resultDataCID matches getJob").is_err());
+        assert_eq!(outcome(&checks, "journal hash matches JobProofVerified"), Ok(()));
+
+        let checks = submission_checks(&calldata(&seal, [0; 32], "bafyresult"), U256::from(8u64), [0; 32], "bafyresult", method_id, &result).unwrap();
+        assert!(outcome(&checks, "result is for this job").is_err());
+        assert!(outcome(&checks, "journal hash matches JobProofVerified").is_err());
+
+        let checks = submission_checks(&calldata(&[0x12, 0x34, 0x56, 0x78], journal_hash, "bafyresult"), job_id, journal_hash, "bafyresult", method_id, &result).unwrap();
+        assert!(outcome(&checks, "on-chain seal verifies against methodId and journal").is_err());
+
+        assert!(submission_checks(&[0xde, 0xad, 0xbe, 0xef], job_id, journal_hash, "bafyresult", method_id, &result).is_err());
+    }
+}
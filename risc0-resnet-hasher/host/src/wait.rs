@@ -0,0 +1,163 @@
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use clap::Args;
+use ethers::prelude::*;
+use eyre::Result;
+
+/// How long to wait for transactions and the state they change.
+#[derive(Args, Debug, Clone)]
+pub struct WaitArgs {
+    /// Blocks a transaction must be buried under (its own included) before it counts as final.
+    /// At least one block on top of it is always waited for, so a reorg of the tip is noticed.
+    #[arg(long, global = true, env = "CONFIRMATIONS", default_value_t = 1)]
+    pub confirmations: u64,
+    /// Give up waiting for confirmations or on-chain state after this many seconds.
+    #[arg(long, global = true, default_value_t = 300)]
+    pub wait_timeout_secs: u64,
+    /// Milliseconds between polls while waiting.
+    #[arg(long, global = true, default_value_t = 1000)]
+    pub poll_interval_ms: u64,
+}
+
+impl WaitArgs {
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(self.wait_timeout_secs)
+    }
+
+    fn poll_interval(&self) -> Duration {
+        Duration::from_millis(self.poll_interval_ms)
+    }
+}
+
+/// Calls `probe` until it returns `Some`, or fails once `--wait-timeout-secs` have passed. Errors
+/// from `probe`, usually RPC hiccups, are logged and polled through until then.
+pub async fn poll_until<T, F, Fut>(wait: &WaitArgs, what: &str, mut probe: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Option<T>>>,
+{
+    let deadline = Instant::now() + wait.timeout();
+    let mut last_error = None;
+    loop {
+        match probe().await {
+            Ok(Some(value)) => return Ok(value),
+            Ok(None) => {}
+            Err(e) => {
+                println!("⚠️ Error while waiting for {}: {:#}; retrying.", what, e);
+                last_error = Some(e);
+            }
+        }
+        if Instant::now() >= deadline {
+            return Err(match last_error {
+                Some(e) => e.wrap_err(format!("Timed out after {}s waiting for {}", wait.wait_timeout_secs, what)),
+                None => eyre::eyre!("Timed out after {}s waiting for {}.", wait.wait_timeout_secs, what),
+            });
+        }
+        tokio::time::sleep(wait.poll_interval()).await;
+    }
+}
+
+/// Waits until `receipt`'s transaction has `--confirmations` blocks on top of it, and always at
+/// least one besides its own, and returns the receipt as it stands then. A reorg that moves the
+/// transaction to another block is followed, and one that drops it is waited out until it is
+/// mined again; a reorg that makes it revert is an error.
+pub async fn wait_for_confirmations<M: Middleware>(client: &M, wait: &WaitArgs, what: &str, receipt: TransactionReceipt) -> Result<TransactionReceipt> {
+    let confirmations = wait.confirmations.max(2);
+    let tx_hash = receipt.transaction_hash;
+    let mut block_hash = receipt.block_hash;
+    println!("Waiting for {} confirmations of {}...", confirmations, what);
+    let deadline = Instant::now() + wait.timeout();
+    loop {
+        // RPC errors are logged and retried: the receipt is already mined, so only the timeout
+        // should end the wait.
+        match client.get_transaction_receipt(tx_hash).await {
+            Err(e) => println!("⚠️ Receipt of {:?}: {}; retrying.", tx_hash, e),
+            Ok(None) => println!("⚠️ {} ({:?}) dropped out of the chain in a reorg; waiting for it to be mined again.", what, tx_hash),
+            Ok(Some(current)) => {
+                if current.block_hash != block_hash {
+                    println!("⚠️ {} was reorged into block {:?}.", what, current.block_number.unwrap_or_default());
+                    block_hash = current.block_hash;
+                }
+                if current.status != Some(1.into()) {
+                    eyre::bail!("{} reverted after a reorg. Tx: {:?}", what, tx_hash);
+                }
+                match (current.block_number, client.get_block_number().await) {
+                    (Some(mined_in), Ok(head)) if head.as_u64() + 1 >= mined_in.as_u64() + confirmations => return Ok(current),
+                    (_, Err(e)) => println!("⚠️ Block number: {}; retrying.", e),
+                    _ => {}
+                }
+            }
+        }
+        if Instant::now() >= deadline {
+            eyre::bail!("Timed out after {}s waiting for {} confirmations of {}. Tx: {:?}", wait.wait_timeout_secs, confirmations, what, tx_hash);
+        }
+        tokio::time::sleep(wait.poll_interval()).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[tokio::test]
+    async fn poll_until_returns_first_value_or_times_out() {
+        let wait = WaitArgs { confirmations: 1, wait_timeout_secs: 0, poll_interval_ms: 1 };
+        let polls = Cell::new(0);
+        let value = poll_until(&WaitArgs { wait_timeout_secs: 5, ..wait.clone() }, "third poll", || {
+            polls.set(polls.get() + 1);
+            let poll = polls.get();
+            async move { Ok((poll == 3).then_some(poll)) }
+        })
+        .await
+        .unwrap();
+        assert_eq!(value, 3);
+
+        let err = poll_until(&wait, "nothing", || async { Ok(None::<()>) }).await.unwrap_err();
+        assert_eq!(err.to_string(), "Timed out after 0s waiting for nothing.");
+
+        // An RPC error is retried, not returned.
+        let polls = Cell::new(0);
+        let value = poll_until(&WaitArgs { wait_timeout_secs: 5, ..wait.clone() }, "recovery", || {
+            polls.set(polls.get() + 1);
+            let poll = polls.get();
+            async move { if poll == 1 { eyre::bail!("connection reset") } else { Ok(Some(poll)) } }
+        })
+        .await
+        .unwrap();
+        assert_eq!(value, 2);
+    }
+
+    fn mined(block_number: u64, block_hash: H256, status: u64) -> TransactionReceipt {
+        TransactionReceipt {
+            transaction_hash: H256::repeat_byte(0xaa),
+            block_number: Some(block_number.into()),
+            block_hash: Some(block_hash),
+            status: Some(status.into()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn confirmations_follow_reorgs_and_survive_rpc_errors() {
+        let wait = WaitArgs { confirmations: 1, wait_timeout_secs: 5, poll_interval_ms: 1 };
+        let (provider, mock) = Provider::mocked();
+        // MockProvider answers from the back, so responses are pushed last call first.
+        mock.push(U64::from(13)).unwrap(); // head once the reorged block has one on top
+        mock.push(mined(12, H256::repeat_byte(2), 1)).unwrap();
+        mock.push(U64::from(12)).unwrap(); // reorged block is still the head
+        mock.push(mined(12, H256::repeat_byte(2), 1)).unwrap(); // mined again in another block
+        mock.push(None::<TransactionReceipt>).unwrap(); // dropped by the reorg
+        mock.push_response(MockResponse::Error(JsonRpcError { code: -32000, message: "header not found".into(), data: None }));
+
+        // Even at --confirmations 1, the wait lasts until a block is on top.
+        let receipt = wait_for_confirmations(&provider, &wait, "test tx", mined(10, H256::repeat_byte(1), 1)).await.unwrap();
+        assert_eq!((receipt.block_number, receipt.block_hash), (Some(12.into()), Some(H256::repeat_byte(2))));
+
+        let (provider, mock) = Provider::mocked();
+        mock.push(mined(11, H256::repeat_byte(3), 0)).unwrap();
+        let err = wait_for_confirmations(&provider, &wait, "test tx", mined(10, H256::repeat_byte(1), 1)).await.unwrap_err();
+        assert!(err.to_string().contains("reverted after a reorg"), "{}", err);
+    }
+}
@@ -2,18 +2,84 @@
 #![no_std]
 extern crate alloc; // If JobInputs/JobOutputs use Vec, etc.
 
+pub mod resnet;
+pub mod sampling;
+pub mod weights;
+
 use serde::{Deserialize, Serialize};
 use alloc::vec::Vec; // Assuming your structs use Vec<u8>
 
+/// How the host turned decoded images into `image_batch_data`, so a client can rerun it.
+///
+/// Each image is resized so its shorter side is `resize`, center-cropped to
+/// `crop_height x crop_width`, and every pixel of channel `c` becomes
+/// `round(((p / 255) - mean[c]) / std[c] / input_scale)`, saturated to int8, in NCHW order.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Preprocessing {
+    pub resize: u32,
+    pub crop_height: u32,
+    pub crop_width: u32,
+    pub mean: Vec<f32>,
+    pub std: Vec<f32>,
+    /// Normalized value of one int8 step; the model's input quantization scale.
+    pub input_scale: f32,
+}
+
+/// Identifies the on-chain job a proof was made for, so its receipt cannot claim another job
+/// that uses the same `methodId`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct JobBinding {
+    /// `jobId` as a big-endian uint256.
+    pub job_id: [u8; 32],
+    pub chain_id: u64,
+    pub job_manager: [u8; 20],
+    pub client: [u8; 20],
+    /// `keccak256(blockhash(n - 1) ‖ jobId ‖ provider)`, `n` being the `JobCreated` block.
+    pub seed: [u8; 32],
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JobInputs {
+    pub binding: JobBinding,
+    /// int8 NCHW images, each `ResNetConfig::image_len()` bytes.
     pub image_batch_data: Vec<u8>,
+    /// Model file in the `weights` format (`RZNN` header followed by the tensors).
     pub model_weights_data: Vec<u8>,
+    pub preprocessing: Preprocessing,
+    /// Number of images to spot-check, drawn from the batch with `binding.seed`; 0 runs them all.
+    pub sample_size: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct JobOutputs {
+    /// Copied from `JobInputs` unchanged.
+    pub binding: JobBinding,
     pub image_batch_hash: [u8; 32],
     pub model_weights_hash: [u8; 32],
-    pub computation_output_hash: [u8; 32],
-}
\ No newline at end of file
+    /// Copied from `JobInputs`; checked against the model's input shape.
+    pub preprocessing: Preprocessing,
+    /// Images in the whole batch covered by `image_batch_hash`.
+    pub batch_size: u32,
+    /// Images that were run, from `sampling::sample_indices(binding.seed, batch_size, sample_size)`.
+    pub sampled_indices: Vec<u32>,
+    pub num_classes: u32,
+    /// Class logits of every sampled image, `num_classes` per image in `sampled_indices` order.
+    pub logits: Vec<i32>,
+}
+
+impl JobOutputs {
+    /// Index of the highest logit of each image.
+    pub fn predicted_classes(&self) -> Vec<usize> {
+        if self.num_classes == 0 {
+            return Vec::new();
+        }
+        self.logits
+            .chunks(self.num_classes as usize)
+            .map(|row| {
+                row.iter()
+                    .enumerate()
+                    .fold(0, |best, (class, &logit)| if logit > row[best] { class } else { best })
+            })
+            .collect()
+    }
+}
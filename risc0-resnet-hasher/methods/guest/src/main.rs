@@ -4,15 +4,14 @@
 
 extern crate alloc;
 
-use alloc::vec::Vec;
-// No need to import Box anymore
-// use alloc::boxed::Box;
-
 use risc0_zkvm::guest::env;
 use risc0_zkvm::sha::Impl as ShaImpl;     // The concrete SHA implementation
 use risc0_zkvm::sha::Digest as Risc0Digest; // The Digest type
 use risc0_zkvm::sha::Sha256;              // The Sha256 trait
 
+use method::resnet::ResNet;
+use method::sampling::sample_indices;
+use method::weights::ModelFile;
 use method::{JobInputs, JobOutputs};
 
 risc0_zkvm::guest::entry!(main);
@@ -30,18 +29,35 @@ pub fn main() {
     let weights_digest_val: Risc0Digest = *weights_digest_ref;
     let weights_hash_bytes: [u8; 32] = weights_digest_val.into();
 
-    let mut combined_hashes_data: Vec<u8> = Vec::new();
-    combined_hashes_data.extend_from_slice(&image_hash_bytes);
-    combined_hashes_data.extend_from_slice(&weights_hash_bytes);
-
-    let computation_output_digest_ref: &mut Risc0Digest = ShaImpl::hash_bytes(&combined_hashes_data);
-    let computation_output_digest_val: Risc0Digest = *computation_output_digest_ref;
-    let computation_output_hash_bytes: [u8; 32] = computation_output_digest_val.into();
+    let model_file = ModelFile::parse(&inputs.model_weights_data).expect("model_weights_data is not a model file");
+    let model = ResNet::from_bytes(model_file.config, model_file.tensors).expect("model file tensors are malformed");
+    let config = model.config();
+    let preprocessing = inputs.preprocessing;
+    assert!(
+        preprocessing.crop_height as usize == config.height && preprocessing.crop_width as usize == config.width,
+        "preprocessing crop does not match the model's input size"
+    );
+    assert!(
+        preprocessing.mean.len() == config.in_channels && preprocessing.std.len() == config.in_channels,
+        "preprocessing mean/std do not match the model's input channels"
+    );
+    let batch_size = model
+        .batch_size(&inputs.image_batch_data)
+        .expect("image_batch_data does not match the model's input shape") as u32;
+    let sampled_indices = sample_indices(&inputs.binding.seed, batch_size, inputs.sample_size);
+    let logits = model
+        .forward_selected(&inputs.image_batch_data, &sampled_indices)
+        .expect("sampled index outside the batch");
 
     let outputs = JobOutputs {
+        binding: inputs.binding,
         image_batch_hash: image_hash_bytes,
         model_weights_hash: weights_hash_bytes,
-        computation_output_hash: computation_output_hash_bytes,
+        preprocessing,
+        batch_size,
+        sampled_indices,
+        num_classes: config.num_classes as u32,
+        logits,
     };
     env::commit(&outputs);
 }
\ No newline at end of file
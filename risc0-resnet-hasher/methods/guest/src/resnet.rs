@@ -0,0 +1,425 @@
+// methods/guest/src/resnet.rs
+//! Integer-only ResNet forward pass.
+//!
+//! Activations and weights are int8, accumulators are int32. Every convolution carries an int32
+//! bias and a requantization step `(acc * multiplier) >> shift` (round half up, clamped to int8)
+//! that brings the accumulator back to the activation scale. The residual add assumes both
+//! branches share that scale, so the quantizer must emit matching multipliers. The classifier
+//! is not requantized: its int32 accumulators are the logits.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Shape of a CIFAR-style ResNet: a 3x3 stem, stages of basic blocks, global average pooling
+/// and a linear classifier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResNetConfig {
+    pub in_channels: usize,
+    pub height: usize,
+    pub width: usize,
+    pub stem_channels: usize,
+    /// Output channels of each stage. Every stage after the first halves the spatial size.
+    pub stage_channels: Vec<usize>,
+    pub blocks_per_stage: usize,
+    pub num_classes: usize,
+}
+
+/// Shape of one convolution. Padding is always `kernel / 2`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConvShape {
+    pub in_channels: usize,
+    pub out_channels: usize,
+    pub kernel: usize,
+    pub stride: usize,
+}
+
+impl ConvShape {
+    fn new(in_channels: usize, out_channels: usize, kernel: usize, stride: usize) -> Self {
+        Self { in_channels, out_channels, kernel, stride }
+    }
+
+    /// Bytes taken by this layer in the weights blob: int8 kernel, int32 bias, multiplier, shift.
+    pub fn encoded_len(&self) -> usize {
+        self.out_channels * self.in_channels * self.kernel * self.kernel + self.out_channels * 4 + 8
+    }
+}
+
+impl ResNetConfig {
+    /// ResNet-8 for 3x32x32 inputs and 10 classes (~78k parameters).
+    pub fn resnet8() -> Self {
+        Self {
+            in_channels: 3,
+            height: 32,
+            width: 32,
+            stem_channels: 16,
+            stage_channels: vec![16, 32, 64],
+            blocks_per_stage: 1,
+            num_classes: 10,
+        }
+    }
+
+    /// Bytes in one int8 NCHW image.
+    pub fn image_len(&self) -> usize {
+        self.in_channels * self.height * self.width
+    }
+
+    /// Every convolution in the order the weights blob stores them. Within a block that is
+    /// `conv_a`, `conv_b`, then the 1x1 projection shortcut if the block needs one.
+    pub fn conv_shapes(&self) -> Vec<ConvShape> {
+        let mut shapes = vec![ConvShape::new(self.in_channels, self.stem_channels, 3, 1)];
+        let mut channels = self.stem_channels;
+        for (stage, &out_channels) in self.stage_channels.iter().enumerate() {
+            for block in 0..self.blocks_per_stage {
+                let stride = if stage > 0 && block == 0 { 2 } else { 1 };
+                shapes.push(ConvShape::new(channels, out_channels, 3, stride));
+                shapes.push(ConvShape::new(out_channels, out_channels, 3, 1));
+                if stride != 1 || channels != out_channels {
+                    shapes.push(ConvShape::new(channels, out_channels, 1, stride));
+                }
+                channels = out_channels;
+            }
+        }
+        shapes
+    }
+
+    /// Channels entering the classifier.
+    pub fn feature_channels(&self) -> usize {
+        self.stage_channels.last().copied().unwrap_or(self.stem_channels)
+    }
+
+    /// Total size of the weights blob: every convolution, then the classifier's int8 weights
+    /// (`num_classes x feature_channels`) and int32 bias.
+    pub fn weights_len(&self) -> usize {
+        let convs: usize = self.conv_shapes().iter().map(ConvShape::encoded_len).sum();
+        convs + self.num_classes * self.feature_channels() + self.num_classes * 4
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModelError {
+    /// The weights blob is not exactly `ResNetConfig::weights_len()` bytes.
+    WeightsLength { expected: usize, actual: usize },
+    /// A requantization shift outside `1..=62`.
+    InvalidShift { layer: usize, shift: u32 },
+    /// The image batch is empty or not a whole number of images.
+    BatchLength { image_len: usize, actual: usize },
+    /// A selected image index past the end of the batch.
+    ImageIndex { index: usize, batch_size: usize },
+    /// The model file does not start with `weights::MAGIC`.
+    BadMagic,
+    /// The model file was written by a format version this guest cannot read.
+    UnsupportedVersion(u32),
+    /// The model file ends inside its header.
+    Truncated,
+    /// A header field is zero or above the supported maximum.
+    InvalidHeader { field: &'static str, value: u32 },
+    /// A tensor handed to `ModelWriter` does not match the layer's shape.
+    TensorShape { layer: usize, expected: usize, actual: usize },
+    /// `ModelWriter` got more or fewer convolutions than the architecture has.
+    LayerCount { expected: usize },
+}
+
+/// A convolution borrowing its parameters from the weights blob.
+struct Conv<'a> {
+    shape: ConvShape,
+    weights: &'a [u8],
+    bias: &'a [u8],
+    multiplier: i32,
+    shift: u32,
+}
+
+struct BasicBlock<'a> {
+    conv_a: Conv<'a>,
+    conv_b: Conv<'a>,
+    shortcut: Option<Conv<'a>>,
+}
+
+/// int8 activations in CHW order.
+struct Tensor {
+    data: Vec<i8>,
+    channels: usize,
+    height: usize,
+    width: usize,
+}
+
+/// A ResNet whose parameters are borrowed from the weights blob; nothing is copied.
+pub struct ResNet<'a> {
+    config: ResNetConfig,
+    stem: Conv<'a>,
+    blocks: Vec<BasicBlock<'a>>,
+    fc_weights: &'a [u8],
+    fc_bias: &'a [u8],
+}
+
+/// Splits consecutive fields off the front of the weights blob.
+struct Cursor<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, len: usize) -> &'a [u8] {
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        head
+    }
+
+    fn read_u32(&mut self) -> u32 {
+        let bytes = self.take(4);
+        u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+
+    fn conv(&mut self, shape: ConvShape, layer: usize) -> Result<Conv<'a>, ModelError> {
+        let weights = self.take(shape.out_channels * shape.in_channels * shape.kernel * shape.kernel);
+        let bias = self.take(shape.out_channels * 4);
+        let multiplier = self.read_u32() as i32;
+        let shift = self.read_u32();
+        if !(1..=62).contains(&shift) {
+            return Err(ModelError::InvalidShift { layer, shift });
+        }
+        Ok(Conv { shape, weights, bias, multiplier, shift })
+    }
+}
+
+fn read_i32(bytes: &[u8], index: usize) -> i32 {
+    let at = index * 4;
+    i32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+}
+
+/// Maps an int32 accumulator back to int8: `round((acc * multiplier) / 2^shift)`, saturated.
+pub fn requantize(acc: i32, multiplier: i32, shift: u32) -> i8 {
+    let scaled = (acc as i64 * multiplier as i64 + (1i64 << (shift - 1))) >> shift;
+    scaled.clamp(i8::MIN as i64, i8::MAX as i64) as i8
+}
+
+impl Conv<'_> {
+    fn forward(&self, input: &Tensor, relu: bool) -> Tensor {
+        let ConvShape { in_channels, out_channels, kernel, stride } = self.shape;
+        let pad = kernel / 2;
+        let out_height = (input.height + 2 * pad - kernel) / stride + 1;
+        let out_width = (input.width + 2 * pad - kernel) / stride + 1;
+        let mut data = Vec::with_capacity(out_channels * out_height * out_width);
+
+        for oc in 0..out_channels {
+            let bias = read_i32(self.bias, oc);
+            let filter = &self.weights[oc * in_channels * kernel * kernel..(oc + 1) * in_channels * kernel * kernel];
+            for oy in 0..out_height {
+                for ox in 0..out_width {
+                    let mut acc = bias;
+                    for ic in 0..in_channels {
+                        let plane = &input.data[ic * input.height * input.width..(ic + 1) * input.height * input.width];
+                        for ky in 0..kernel {
+                            let iy = (oy * stride + ky) as isize - pad as isize;
+                            if iy < 0 || iy >= input.height as isize {
+                                continue;
+                            }
+                            let row = &plane[iy as usize * input.width..(iy as usize + 1) * input.width];
+                            for kx in 0..kernel {
+                                let ix = (ox * stride + kx) as isize - pad as isize;
+                                if ix < 0 || ix >= input.width as isize {
+                                    continue;
+                                }
+                                let w = filter[(ic * kernel + ky) * kernel + kx] as i8;
+                                acc = acc.wrapping_add(w as i32 * row[ix as usize] as i32);
+                            }
+                        }
+                    }
+                    let out = requantize(acc, self.multiplier, self.shift);
+                    data.push(if relu { out.max(0) } else { out });
+                }
+            }
+        }
+        Tensor { data, channels: out_channels, height: out_height, width: out_width }
+    }
+}
+
+impl BasicBlock<'_> {
+    fn forward(&self, input: &Tensor) -> Tensor {
+        let hidden = self.conv_a.forward(input, true);
+        let mut out = self.conv_b.forward(&hidden, false);
+        let shortcut = self.shortcut.as_ref().map(|conv| conv.forward(input, false));
+        let identity = shortcut.as_ref().unwrap_or(input);
+        for (o, s) in out.data.iter_mut().zip(&identity.data) {
+            *o = o.saturating_add(*s).max(0);
+        }
+        out
+    }
+}
+
+impl<'a> ResNet<'a> {
+    /// Borrows every layer from `weights`, which must be laid out as described by
+    /// [ResNetConfig::conv_shapes] followed by the classifier.
+    pub fn from_bytes(config: ResNetConfig, weights: &'a [u8]) -> Result<Self, ModelError> {
+        let expected = config.weights_len();
+        if weights.len() != expected {
+            return Err(ModelError::WeightsLength { expected, actual: weights.len() });
+        }
+        let mut cursor = Cursor { data: weights };
+        let mut shapes = config.conv_shapes().into_iter().enumerate();
+        let mut next_conv = |cursor: &mut Cursor<'a>| {
+            let (layer, shape) = shapes.next().expect("conv_shapes covers every layer");
+            cursor.conv(shape, layer)
+        };
+
+        let stem = next_conv(&mut cursor)?;
+        let mut blocks = Vec::new();
+        let mut channels = config.stem_channels;
+        for (stage, &out_channels) in config.stage_channels.iter().enumerate() {
+            for block in 0..config.blocks_per_stage {
+                let conv_a = next_conv(&mut cursor)?;
+                let conv_b = next_conv(&mut cursor)?;
+                let needs_projection = (stage > 0 && block == 0) || channels != out_channels;
+                let shortcut = if needs_projection { Some(next_conv(&mut cursor)?) } else { None };
+                blocks.push(BasicBlock { conv_a, conv_b, shortcut });
+                channels = out_channels;
+            }
+        }
+        let fc_weights = cursor.take(config.num_classes * config.feature_channels());
+        let fc_bias = cursor.take(config.num_classes * 4);
+        Ok(Self { config, stem, blocks, fc_weights, fc_bias })
+    }
+
+    pub fn config(&self) -> &ResNetConfig {
+        &self.config
+    }
+
+    /// Class logits for one int8 CHW image of `config.image_len()` bytes.
+    pub fn forward(&self, image: &[u8]) -> Vec<i32> {
+        let input = Tensor {
+            data: image.iter().map(|&b| b as i8).collect(),
+            channels: self.config.in_channels,
+            height: self.config.height,
+            width: self.config.width,
+        };
+        let mut x = self.stem.forward(&input, true);
+        for block in &self.blocks {
+            x = block.forward(&x);
+        }
+
+        // Global average pooling, rounded to nearest.
+        let area = (x.height * x.width) as i32;
+        let pooled: Vec<i32> = x
+            .data
+            .chunks(x.height * x.width)
+            .map(|plane| {
+                let sum: i32 = plane.iter().map(|&v| v as i32).sum();
+                (sum + area / 2).div_euclid(area)
+            })
+            .collect();
+        debug_assert_eq!(pooled.len(), x.channels);
+
+        (0..self.config.num_classes)
+            .map(|class| {
+                let row = &self.fc_weights[class * pooled.len()..(class + 1) * pooled.len()];
+                row.iter()
+                    .zip(&pooled)
+                    .fold(read_i32(self.fc_bias, class), |acc, (&w, &p)| acc.wrapping_add(w as i8 as i32 * p))
+            })
+            .collect()
+    }
+
+    /// Number of images in an int8 NCHW batch.
+    pub fn batch_size(&self, images: &[u8]) -> Result<usize, ModelError> {
+        let image_len = self.config.image_len();
+        if images.is_empty() || !images.len().is_multiple_of(image_len) {
+            return Err(ModelError::BatchLength { image_len, actual: images.len() });
+        }
+        Ok(images.len() / image_len)
+    }
+
+    /// Runs every image of an int8 NCHW batch and returns the logits image by image.
+    pub fn forward_batch(&self, images: &[u8]) -> Result<Vec<i32>, ModelError> {
+        self.batch_size(images)?;
+        Ok(images.chunks(self.config.image_len()).flat_map(|image| self.forward(image)).collect())
+    }
+
+    /// Runs only the images at `indices` and returns their logits in that order.
+    pub fn forward_selected(&self, images: &[u8], indices: &[u32]) -> Result<Vec<i32>, ModelError> {
+        let batch_size = self.batch_size(images)?;
+        let image_len = self.config.image_len();
+        let mut logits = Vec::with_capacity(indices.len() * self.config.num_classes);
+        for &index in indices {
+            let index = index as usize;
+            if index >= batch_size {
+                return Err(ModelError::ImageIndex { index, batch_size });
+            }
+            logits.extend(self.forward(&images[index * image_len..(index + 1) * image_len]));
+        }
+        Ok(logits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tiny_config() -> ResNetConfig {
+        ResNetConfig {
+            in_channels: 1,
+            height: 4,
+            width: 4,
+            stem_channels: 2,
+            stage_channels: vec![2, 4],
+            blocks_per_stage: 1,
+            num_classes: 3,
+        }
+    }
+
+    /// Weights that pass each conv's first input channel straight through (centre tap 1,
+    /// multiplier 1, shift 1 halves it) with a classifier summing every pooled channel.
+    fn passthrough_weights(config: &ResNetConfig) -> Vec<u8> {
+        let mut blob = Vec::new();
+        for shape in config.conv_shapes() {
+            let centre = (shape.kernel / 2) * shape.kernel + shape.kernel / 2;
+            for _ in 0..shape.out_channels {
+                for ic in 0..shape.in_channels {
+                    for tap in 0..shape.kernel * shape.kernel {
+                        blob.push(u8::from(ic == 0 && tap == centre));
+                    }
+                }
+            }
+            blob.extend(core::iter::repeat(0).take(shape.out_channels * 4));
+            blob.extend_from_slice(&1i32.to_le_bytes());
+            blob.extend_from_slice(&1u32.to_le_bytes());
+        }
+        blob.extend(core::iter::repeat(1).take(config.num_classes * config.feature_channels()));
+        for class in 0..config.num_classes as i32 {
+            blob.extend_from_slice(&(class * 100).to_le_bytes());
+        }
+        blob
+    }
+
+    #[test]
+    fn requantize_rounds_and_saturates() {
+        assert_eq!(requantize(5, 1, 1), 3);
+        assert_eq!(requantize(-5, 1, 1), -2);
+        assert_eq!(requantize(1 << 20, 1, 1), i8::MAX);
+        assert_eq!(requantize(-(1 << 20), 1, 1), i8::MIN);
+    }
+
+    #[test]
+    fn rejects_wrong_sized_weights() {
+        let config = tiny_config();
+        let expected = config.weights_len();
+        let err = ResNet::from_bytes(config, &[0u8; 7]).err().unwrap();
+        assert_eq!(err, ModelError::WeightsLength { expected, actual: 7 });
+    }
+
+    #[test]
+    fn forward_batch_yields_logits_per_image() {
+        let config = tiny_config();
+        let weights = passthrough_weights(&config);
+        let model = ResNet::from_bytes(config.clone(), &weights).unwrap();
+
+        let zeros = vec![0u8; config.image_len()];
+        assert_eq!(model.forward(&zeros), vec![0, 100, 200]);
+
+        let mut batch = vec![64u8; config.image_len()];
+        batch.extend_from_slice(&zeros);
+        let logits = model.forward_batch(&batch).unwrap();
+        assert_eq!(logits.len(), 2 * config.num_classes);
+        assert!(logits[0] > 0);
+        assert_eq!(&logits[3..], &[0, 100, 200]);
+
+        assert!(model.forward_batch(&batch[1..]).is_err());
+    }
+}
@@ -0,0 +1,57 @@
+// methods/guest/src/sampling.rs
+//! Seed-driven spot-check sampling.
+//!
+//! The seed is fixed by the chain before the provider sees the job, so the provider cannot pick
+//! which images get proven. Anyone holding the seed can recompute the same indices.
+
+use alloc::vec::Vec;
+
+use risc0_zkvm::sha::{Impl as ShaImpl, Sha256};
+
+/// Draws `count` distinct indices from `0..population`, sorted ascending.
+///
+/// A partial Fisher-Yates shuffle driven by `sha256(seed ‖ round)`, where `round` is the
+/// little-endian u32 position being filled. `count == 0` or `count >= population` selects every
+/// index.
+pub fn sample_indices(seed: &[u8; 32], population: u32, count: u32) -> Vec<u32> {
+    let mut indices: Vec<u32> = (0..population).collect();
+    if count == 0 || count >= population {
+        return indices;
+    }
+    let mut message = [0u8; 36];
+    message[..32].copy_from_slice(seed);
+    for round in 0..count {
+        message[32..].copy_from_slice(&round.to_le_bytes());
+        let digest = *ShaImpl::hash_bytes(&message);
+        let bytes = digest.as_bytes();
+        let draw = u64::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7]]);
+        let remaining = (population - round) as u64;
+        let pick = round + (draw % remaining) as u32;
+        indices.swap(round as usize, pick as usize);
+    }
+    indices.truncate(count as usize);
+    indices.sort_unstable();
+    indices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_are_distinct_sorted_and_reproducible() {
+        let seed = [7u8; 32];
+        let sample = sample_indices(&seed, 1000, 16);
+        assert_eq!(sample.len(), 16);
+        assert!(sample.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(sample.iter().all(|&index| index < 1000));
+        assert_eq!(sample, sample_indices(&seed, 1000, 16));
+        assert_ne!(sample, sample_indices(&[8u8; 32], 1000, 16));
+    }
+
+    #[test]
+    fn zero_or_oversized_count_selects_everything() {
+        assert_eq!(sample_indices(&[0u8; 32], 4, 0), [0, 1, 2, 3]);
+        assert_eq!(sample_indices(&[0u8; 32], 4, 9), [0, 1, 2, 3]);
+    }
+}
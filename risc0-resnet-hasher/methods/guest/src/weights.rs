@@ -0,0 +1,211 @@
+// methods/guest/src/weights.rs
+//! Versioned model-weight file shared by the host and the guest.
+//!
+//! ```text
+//! magic      b"RZNN"
+//! version    u32            FORMAT_VERSION
+//! config     u32 x 7        in_channels, height, width, stem_channels,
+//!                           blocks_per_stage, num_classes, stage_count
+//! stages     u32 x stage_count
+//! tensors    ResNetConfig::weights_len() bytes
+//! ```
+//!
+//! All integers are little-endian. The tensor section is, for every layer of
+//! [ResNetConfig::conv_shapes] in order, the int8 `[out][in][k][k]` kernel, the int32 bias per
+//! output channel and the requantization multiplier (i32) and shift (u32); then the classifier's
+//! int8 `[classes][features]` weights and int32 bias. Since the header pins the architecture, the
+//! `model_weights_hash` committed by the guest identifies both the network and its parameters.
+
+use alloc::vec::Vec;
+
+use crate::resnet::{ConvShape, ModelError, ResNetConfig};
+
+pub const MAGIC: [u8; 4] = *b"RZNN";
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Upper bounds on header fields, so a hostile header cannot overflow the size computations.
+const MAX_DIM: u32 = 1024;
+const MAX_CHANNELS: u32 = 1024;
+const MAX_BLOCKS_PER_STAGE: u32 = 16;
+const MAX_STAGES: u32 = 8;
+const MAX_CLASSES: u32 = 65_536;
+
+/// A parsed model file. `tensors` borrows from the input buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelFile<'a> {
+    pub version: u32,
+    pub config: ResNetConfig,
+    pub tensors: &'a [u8],
+}
+
+fn read_u32(bytes: &[u8], offset: &mut usize) -> Result<u32, ModelError> {
+    let field = bytes.get(*offset..*offset + 4).ok_or(ModelError::Truncated)?;
+    *offset += 4;
+    Ok(u32::from_le_bytes([field[0], field[1], field[2], field[3]]))
+}
+
+fn bounded(value: u32, max: u32, field: &'static str) -> Result<usize, ModelError> {
+    if value == 0 || value > max {
+        return Err(ModelError::InvalidHeader { field, value });
+    }
+    Ok(value as usize)
+}
+
+impl<'a> ModelFile<'a> {
+    /// Checks the header and returns the architecture and a view of the tensor section.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, ModelError> {
+        if bytes.get(..4) != Some(&MAGIC[..]) {
+            return Err(ModelError::BadMagic);
+        }
+        let mut offset = 4;
+        let version = read_u32(bytes, &mut offset)?;
+        if version != FORMAT_VERSION {
+            return Err(ModelError::UnsupportedVersion(version));
+        }
+        let in_channels = bounded(read_u32(bytes, &mut offset)?, MAX_CHANNELS, "in_channels")?;
+        let height = bounded(read_u32(bytes, &mut offset)?, MAX_DIM, "height")?;
+        let width = bounded(read_u32(bytes, &mut offset)?, MAX_DIM, "width")?;
+        let stem_channels = bounded(read_u32(bytes, &mut offset)?, MAX_CHANNELS, "stem_channels")?;
+        let blocks_per_stage = bounded(read_u32(bytes, &mut offset)?, MAX_BLOCKS_PER_STAGE, "blocks_per_stage")?;
+        let num_classes = bounded(read_u32(bytes, &mut offset)?, MAX_CLASSES, "num_classes")?;
+        let stage_count = bounded(read_u32(bytes, &mut offset)?, MAX_STAGES, "stage_count")?;
+        let mut stage_channels = Vec::with_capacity(stage_count);
+        for _ in 0..stage_count {
+            stage_channels.push(bounded(read_u32(bytes, &mut offset)?, MAX_CHANNELS, "stage_channels")?);
+        }
+
+        let config = ResNetConfig { in_channels, height, width, stem_channels, stage_channels, blocks_per_stage, num_classes };
+        let tensors = &bytes[offset..];
+        let expected = config.weights_len();
+        if tensors.len() != expected {
+            return Err(ModelError::WeightsLength { expected, actual: tensors.len() });
+        }
+        Ok(Self { version, config, tensors })
+    }
+}
+
+/// Builds a model file layer by layer, checking every tensor against the configured shapes.
+pub struct ModelWriter {
+    config: ResNetConfig,
+    shapes: Vec<ConvShape>,
+    next_layer: usize,
+    bytes: Vec<u8>,
+}
+
+impl ModelWriter {
+    pub fn new(config: ResNetConfig) -> Self {
+        let mut bytes = Vec::with_capacity(4 + 4 * (8 + config.stage_channels.len()) + config.weights_len());
+        bytes.extend_from_slice(&MAGIC);
+        for field in [
+            FORMAT_VERSION as usize,
+            config.in_channels,
+            config.height,
+            config.width,
+            config.stem_channels,
+            config.blocks_per_stage,
+            config.num_classes,
+            config.stage_channels.len(),
+        ]
+        .into_iter()
+        .chain(config.stage_channels.iter().copied())
+        {
+            bytes.extend_from_slice(&(field as u32).to_le_bytes());
+        }
+        let shapes = config.conv_shapes();
+        Self { config, shapes, next_layer: 0, bytes }
+    }
+
+    /// Shape of the convolution the next [ModelWriter::conv] call must supply, or `None` once
+    /// only the classifier is left.
+    pub fn next_conv_shape(&self) -> Option<ConvShape> {
+        self.shapes.get(self.next_layer).copied()
+    }
+
+    /// Appends the next convolution. `weights` is `[out][in][k][k]`, `bias` has one entry per
+    /// output channel.
+    pub fn conv(&mut self, weights: &[i8], bias: &[i32], multiplier: i32, shift: u32) -> Result<&mut Self, ModelError> {
+        let layer = self.next_layer;
+        let shape = self.next_conv_shape().ok_or(ModelError::LayerCount { expected: self.shapes.len() })?;
+        let expected_weights = shape.out_channels * shape.in_channels * shape.kernel * shape.kernel;
+        if weights.len() != expected_weights || bias.len() != shape.out_channels {
+            return Err(ModelError::TensorShape { layer, expected: expected_weights, actual: weights.len() });
+        }
+        if !(1..=62).contains(&shift) {
+            return Err(ModelError::InvalidShift { layer, shift });
+        }
+        self.bytes.extend(weights.iter().map(|&w| w as u8));
+        for b in bias {
+            self.bytes.extend_from_slice(&b.to_le_bytes());
+        }
+        self.bytes.extend_from_slice(&multiplier.to_le_bytes());
+        self.bytes.extend_from_slice(&shift.to_le_bytes());
+        self.next_layer += 1;
+        Ok(self)
+    }
+
+    /// Appends the classifier and returns the finished file. Every convolution must be written.
+    pub fn finish(mut self, weights: &[i8], bias: &[i32]) -> Result<Vec<u8>, ModelError> {
+        if self.next_layer != self.shapes.len() {
+            return Err(ModelError::LayerCount { expected: self.shapes.len() });
+        }
+        let expected_weights = self.config.num_classes * self.config.feature_channels();
+        if weights.len() != expected_weights || bias.len() != self.config.num_classes {
+            return Err(ModelError::TensorShape { layer: self.shapes.len(), expected: expected_weights, actual: weights.len() });
+        }
+        self.bytes.extend(weights.iter().map(|&w| w as u8));
+        for b in bias {
+            self.bytes.extend_from_slice(&b.to_le_bytes());
+        }
+        Ok(self.bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn tiny_config() -> ResNetConfig {
+        ResNetConfig {
+            in_channels: 1,
+            height: 4,
+            width: 4,
+            stem_channels: 2,
+            stage_channels: vec![2, 4],
+            blocks_per_stage: 1,
+            num_classes: 3,
+        }
+    }
+
+    fn tiny_model() -> Vec<u8> {
+        let config = tiny_config();
+        let mut writer = ModelWriter::new(config.clone());
+        while let Some(shape) = writer.next_conv_shape() {
+            let taps = shape.out_channels * shape.in_channels * shape.kernel * shape.kernel;
+            writer.conv(&vec![1; taps], &vec![0; shape.out_channels], 1, 4).unwrap();
+        }
+        writer.finish(&vec![1; config.num_classes * config.feature_channels()], &[0, 1, 2]).unwrap()
+    }
+
+    #[test]
+    fn writer_output_parses_back() {
+        let bytes = tiny_model();
+        let file = ModelFile::parse(&bytes).unwrap();
+        assert_eq!(file.version, FORMAT_VERSION);
+        assert_eq!(file.config, tiny_config());
+        assert_eq!(file.tensors.len(), tiny_config().weights_len());
+        assert!(crate::resnet::ResNet::from_bytes(file.config, file.tensors).is_ok());
+    }
+
+    #[test]
+    fn rejects_bad_headers() {
+        let mut bytes = tiny_model();
+        assert_eq!(ModelFile::parse(&bytes[..6]).err(), Some(ModelError::Truncated));
+        assert_eq!(ModelFile::parse(&bytes[..bytes.len() - 1]).err().map(|e| matches!(e, ModelError::WeightsLength { .. })), Some(true));
+
+        bytes[4] = 2;
+        assert_eq!(ModelFile::parse(&bytes).err(), Some(ModelError::UnsupportedVersion(2)));
+        bytes[0] = b'X';
+        assert_eq!(ModelFile::parse(&bytes).err(), Some(ModelError::BadMagic));
+    }
+}
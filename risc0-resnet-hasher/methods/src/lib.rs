@@ -3,7 +3,7 @@ include!(concat!(env!("OUT_DIR"), "/methods.rs"));
 // methods/src/lib.rs
 
 use risc0_zkvm::sha::DIGEST_WORDS;
-pub use method::{JobInputs, JobOutputs};
+pub use method::{resnet, sampling, weights, JobBinding, JobInputs, JobOutputs, Preprocessing};
 
 // Re-export GUEST_ELF under the name your host program expects.
 pub const RISC0_RESNET_HASHER_ELF: &[u8] = METHOD_ELF;
@@ -191,7 +191,7 @@ contract JobManager is AccessControl, ReentrancyGuard {
     function submitProofAndClaim(
         uint256 _jobId,
         bytes calldata _seal,        // The GROTH16 proof seal (output of STARK-to-SNARK pipeline)
-        bytes32 _journalHash,     // Keccak256 hash of the Risc0 journal's public output
+        bytes32 _journalHash,     // SHA-256 digest of the Risc0 journal (what IRiscZeroVerifier.verify expects)
         string memory _resultDataCID  // CID of the actual computation result
     ) public nonReentrant onlyJobProvider(_jobId) { // Ensures job exists and msg.sender is assigned provider
         Job storage job = jobs[_jobId];
@@ -41,14 +41,19 @@ use risc0_zkvm::{sha::Digestible, InnerReceipt};
 #[cfg(not(target_os = "zkvm"))]
 use alloy::{primitives::Bytes, sol_types::SolInterface, transports::TransportError};
 
+#[cfg(feature = "unstable")]
+use alloy::sol_types::SolValue;
+#[cfg(feature = "unstable")]
+use risc0_aggregation::SetInclusionReceipt;
+
 alloy::sol!(
     #![sol(rpc, all_derives)]
-    "src/IRiscZeroVerifier.sol"
+    "IRiscZeroVerifier.sol"
 );
 
 alloy::sol!(
     #![sol(rpc, all_derives)]
-    "src/IRiscZeroSetVerifier.sol"
+    "IRiscZeroSetVerifier.sol"
 );
 
 #[cfg(not(target_os = "zkvm"))]
@@ -80,34 +85,86 @@ pub enum DecodingError {
     Abi(alloy::sol_types::Error, Bytes),
 }
 
+/// Receipt types that [encode_seal] can turn into a selector-prefixed seal.
+pub trait EncodeSeal {
+    /// Encode the receipt as a seal, prefixed with the selector of its verifier.
+    fn encode_seal(&self) -> Result<Vec<u8>>;
+}
+
+impl EncodeSeal for risc0_zkvm::Receipt {
+    fn encode_seal(&self) -> Result<Vec<u8>> {
+        let seal = match self.inner.clone() {
+            InnerReceipt::Fake(receipt) => {
+                let seal = receipt.claim.digest().as_bytes().to_vec();
+                let selector = &[0xFFu8; 4];
+                // Create a new vector with the capacity to hold both selector and seal
+                let mut selector_seal = Vec::with_capacity(selector.len() + seal.len());
+                selector_seal.extend_from_slice(selector);
+                selector_seal.extend_from_slice(&seal);
+                selector_seal
+            }
+            InnerReceipt::Groth16(receipt) => {
+                let selector = &receipt.verifier_parameters.as_bytes()[..4];
+                // Create a new vector with the capacity to hold both selector and seal
+                let mut selector_seal = Vec::with_capacity(selector.len() + receipt.seal.len());
+                selector_seal.extend_from_slice(selector);
+                selector_seal.extend_from_slice(receipt.seal.as_ref());
+                selector_seal
+            }
+            _ => bail!("Unsupported receipt type"),
+        };
+        Ok(seal)
+    }
+}
+
+/// Encodes a set inclusion receipt as a `RiscZeroSetVerifier` seal: the set verifier selector
+/// followed by the ABI-encoded [Seal] holding the Merkle path and, if present, the root seal.
+///
+/// Without a root receipt the root seal is left empty, and the verifier will only accept the seal
+/// once the root has been published with `submitMerkleRoot`.
+#[cfg(feature = "unstable")]
+impl EncodeSeal for SetInclusionReceipt<risc0_zkvm::ReceiptClaim> {
+    fn encode_seal(&self) -> Result<Vec<u8>> {
+        let selector: [u8; 4] = self.verifier_parameters.as_bytes()[..4].try_into()?;
+        match selector::Selector::from_bytes(selector).map(|s| s.get_type()) {
+            Some(selector::SelectorType::SetVerifier) => {}
+            _ => bail!("Unsupported set verifier selector {}", hex::encode(selector)),
+        }
+        let root_seal = match &self.root {
+            Some(root) => root.encode_seal()?,
+            None => Vec::new(),
+        };
+        let seal = Seal {
+            path: self
+                .merkle_path
+                .iter()
+                .map(|digest| <[u8; 32]>::from(*digest).into())
+                .collect(),
+            rootSeal: root_seal.into(),
+        }
+        .abi_encode();
+        // Create a new vector with the capacity to hold both selector and seal
+        let mut selector_seal = Vec::with_capacity(selector.len() + seal.len());
+        selector_seal.extend_from_slice(&selector);
+        selector_seal.extend_from_slice(&seal);
+        Ok(selector_seal)
+    }
+}
+
+impl<T: EncodeSeal + ?Sized> EncodeSeal for Box<T> {
+    fn encode_seal(&self) -> Result<Vec<u8>> {
+        self.as_ref().encode_seal()
+    }
+}
+
 /// Encode the seal of the given receipt for use with EVM smart contract verifiers.
 ///
 /// Appends the verifier selector, determined from the first 4 bytes of the verifier parameters
 /// including the Groth16 verification key and the control IDs that commit to the RISC Zero
-/// circuits.
-pub fn encode_seal(receipt: &risc0_zkvm::Receipt) -> Result<Vec<u8>> {
-    let seal = match receipt.inner.clone() {
-        InnerReceipt::Fake(receipt) => {
-            let seal = receipt.claim.digest().as_bytes().to_vec();
-            let selector = &[0xFFu8; 4];
-            // Create a new vector with the capacity to hold both selector and seal
-            let mut selector_seal = Vec::with_capacity(selector.len() + seal.len());
-            selector_seal.extend_from_slice(selector);
-            selector_seal.extend_from_slice(&seal);
-            selector_seal
-        }
-        InnerReceipt::Groth16(receipt) => {
-            let selector = &receipt.verifier_parameters.as_bytes()[..4];
-            // Create a new vector with the capacity to hold both selector and seal
-            let mut selector_seal = Vec::with_capacity(selector.len() + receipt.seal.len());
-            selector_seal.extend_from_slice(selector);
-            selector_seal.extend_from_slice(receipt.seal.as_ref());
-            selector_seal
-        }
-        _ => bail!("Unsupported receipt type"),
-        // TODO(victor): Add set verifier seal here.
-    };
-    Ok(seal)
+/// circuits. With the `unstable` feature, a [SetInclusionReceipt] is encoded with the selector of
+/// the set verifier instead.
+pub fn encode_seal<R: EncodeSeal + ?Sized>(receipt: &R) -> Result<Vec<u8>> {
+    receipt.encode_seal()
 }
 
 #[cfg(not(target_os = "zkvm"))]
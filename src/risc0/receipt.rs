@@ -36,13 +36,17 @@ pub enum Receipt {
 
 impl Receipt {
     /// Encode the receipt as a seal.
+    ///
+    /// [SetInclusionEncodingError] cannot carry the cause, so it is logged before being dropped.
     pub fn abi_encode_seal(&self) -> Result<Vec<u8>, SetInclusionEncodingError> {
-        match self {
-            Receipt::Base(receipt) => {
-                encode_seal(receipt).map_err(|_| SetInclusionEncodingError::UnsupportedReceipt)
-            }
-            Receipt::SetInclusion(receipt) => receipt.abi_encode_seal(),
-        }
+        let encoded = match self {
+            Receipt::Base(receipt) => encode_seal(receipt),
+            Receipt::SetInclusion(receipt) => encode_seal(receipt),
+        };
+        encoded.map_err(|err| {
+            tracing::warn!("Failed to encode seal: {err:#}");
+            SetInclusionEncodingError::UnsupportedReceipt
+        })
     }
 
     /// Get the receipt if it is a base [risc0_zkvm::Receipt].
@@ -122,3 +126,52 @@ pub fn decode_seal_with_claim(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkvm::sha::Digestible;
+
+    use super::*;
+
+    #[test]
+    fn set_inclusion_seal_round_trip() {
+        let claim = ReceiptClaim::ok(Digest::default(), vec![1, 2, 3]);
+        let merkle_path = vec![Digest::from([1u32; 8]), Digest::from([2u32; 8])];
+        let verifier_parameters = Selector::SetVerifierV0_6.verifier_parameters_digest().unwrap();
+        let receipt = SetInclusionReceipt::from_path_with_verifier_params(
+            claim.clone(),
+            merkle_path.clone(),
+            verifier_parameters,
+        );
+
+        // Selector followed by `abi.encode(Seal({path: merkle_path, rootSeal: ""}))`, built by
+        // hand: offset of the tuple, offsets of its two fields, the path, then an empty rootSeal.
+        let word = |value: u8| {
+            let mut word = [0u8; 32];
+            word[31] = value;
+            word
+        };
+        let mut expected = (Selector::SetVerifierV0_6 as u32).to_be_bytes().to_vec();
+        for value in [0x20, 0x40, 0x40 + 32 * 3, 2] {
+            expected.extend_from_slice(&word(value));
+        }
+        for digest in &merkle_path {
+            expected.extend_from_slice(digest.as_bytes());
+        }
+        expected.extend_from_slice(&word(0));
+
+        let seal = encode_seal(&receipt).unwrap();
+        assert_eq!(hex::encode(&seal), hex::encode(&expected));
+        assert_eq!(
+            Receipt::SetInclusion(Box::new(receipt)).abi_encode_seal().unwrap(),
+            seal
+        );
+
+        let decoded = decode_seal_with_claim(seal.into(), claim.clone(), vec![1, 2, 3]).unwrap();
+        let decoded = decoded.set_inclusion_receipt().unwrap();
+        assert_eq!(decoded.merkle_path, merkle_path);
+        assert_eq!(decoded.verifier_parameters, verifier_parameters);
+        assert_eq!(decoded.claim.digest(), claim.digest());
+        assert!(decoded.root.is_none());
+    }
+}